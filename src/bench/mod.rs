@@ -0,0 +1,106 @@
+use crate::dict::Dictionary;
+use crate::tagger::{AhoCorasickOptions, Backend};
+use crate::traits::PrettyDisplay;
+use colored::Colorize;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Measures of a matcher backend over a corpus
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub backend: Backend,
+    pub build_time: Duration,
+    pub heap_bytes: usize,
+    pub search_time: Duration,
+    pub bytes: usize,
+    pub lines: usize,
+    pub matches: usize,
+}
+
+impl BenchResult {
+    pub fn megabytes_per_second(&self) -> f64 {
+        per_second(self.bytes as f64 / 1_000_000.0, self.search_time)
+    }
+
+    pub fn lines_per_second(&self) -> f64 {
+        per_second(self.lines as f64, self.search_time)
+    }
+}
+
+/// Returns a quantity per second, 0 when the search took no measurable time (e.g. an empty corpus)
+fn per_second(quantity: f64, time: Duration) -> f64 {
+    match time.as_secs_f64() {
+        seconds if seconds > 0.0 => quantity / seconds,
+        _ => 0.0,
+    }
+}
+
+/// Builds each backend over the dictionary terms and searches every line of the corpus
+pub fn bench(
+    dict: &Dictionary,
+    corpus: &[String],
+    backends: &[Backend],
+    case_sensitive: bool,
+    options: &AhoCorasickOptions,
+) -> Result<Vec<BenchResult>, Box<dyn Error>> {
+    let terms = dict.terms();
+    let lines = corpus
+        .iter()
+        .flat_map(|document| document.lines())
+        .collect::<Vec<&str>>();
+
+    backends
+        .iter()
+        .map(|backend| {
+            info!("Benchmarking {} backend", backend);
+
+            let build_start = Instant::now();
+            let matcher = backend.build(&terms, case_sensitive, options)?;
+            let build_time = build_start.elapsed();
+
+            let search_start = Instant::now();
+            let matches = lines.iter().map(|line| matcher.find(line).len()).sum();
+            let search_time = search_start.elapsed();
+
+            Ok(BenchResult {
+                backend: *backend,
+                build_time,
+                heap_bytes: matcher.heap_bytes(),
+                search_time,
+                bytes: lines.iter().map(|line| line.len()).sum(),
+                lines: lines.len(),
+                matches,
+            })
+        })
+        .collect()
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{:.2}\t{:.2}\t{:.2}\t{:.0}\t{}",
+            self.backend,
+            self.build_time.as_secs_f64() * 1000.0,
+            self.heap_bytes as f64 / 1_000_000.0,
+            self.megabytes_per_second(),
+            self.lines_per_second(),
+            self.matches
+        )
+    }
+}
+
+impl PrettyDisplay for BenchResult {
+    fn pretty_display(&self) -> String {
+        format!(
+            "{:<12} {:>12} {:>12} {:>12} {:>14} {:>10}",
+            self.backend.to_string().bold(),
+            format!("{:.2} ms", self.build_time.as_secs_f64() * 1000.0),
+            format!("{:.2} MB", self.heap_bytes as f64 / 1_000_000.0),
+            format!("{:.2} MB/s", self.megabytes_per_second()),
+            format!("{:.0} lines/s", self.lines_per_second()),
+            self.matches
+        )
+    }
+}
@@ -1,9 +1,36 @@
-use clap::{crate_authors, crate_name, crate_version, App, Arg, ArgMatches, SubCommand};
+use clap::{crate_name, crate_version, App, Arg, ArgMatches, SubCommand};
+
+/// Matcher backends of the bytes engine
+const BACKENDS: [&str; 6] = [
+    "aho-corasick",
+    "nfa",
+    "contiguous_nfa",
+    "dfa",
+    "daachorse",
+    "fst",
+];
+
+fn prefilter_arg() -> Arg<'static, 'static> {
+    Arg::with_name("prefilter")
+        .long("prefilter")
+        .takes_value(true)
+        .possible_values(&["true", "false"])
+        .value_name("BOOL")
+        .help("Enables/Disables the prefilter of Aho-Corasick automata")
+}
+
+fn memory_limit_arg() -> Arg<'static, 'static> {
+    Arg::with_name("memory_limit")
+        .long("memory_limit")
+        .takes_value(true)
+        .value_name("MB")
+        .help("Sets the memory limit of each Aho-Corasick automaton, automata above it are rebuilt as contiguous NFAs")
+}
 
 pub fn cli() -> ArgMatches<'static> {
     App::new(crate_name!())
         .version(crate_version!())
-        .author(crate_authors!("\n"))
+        .author(env!("CARGO_PKG_AUTHORS"))
         .about("A BIOES IOB tagger")
         .arg(
             Arg::with_name("dictionary")
@@ -11,9 +38,9 @@ pub fn cli() -> ArgMatches<'static> {
                 .long("dictionary")
                 .alias("dictionaries")
                 .value_name("FILE")
-                .help("Sets dictionaries (TSV format)")
+                .help("Sets dictionaries (TSV format). On equal priorities, earlier dictionaries win duplicated terms and overlapping spans")
                 .multiple(true)
-                .required(true)
+                .required_unless("import")
                 .takes_value(true),
         )
         .arg(
@@ -32,7 +59,175 @@ pub fn cli() -> ArgMatches<'static> {
                 .short("f")
                 .help("Sets the output format")
                 .default_value("iob")
-                .possible_values(&["iob", "IOB", "bioes", "BIOES"]),
+                .possible_values(&[
+                    "iob",
+                    "IOB",
+                    "bioes",
+                    "BIOES",
+                    "iob1",
+                    "IOB1",
+                    "ioe1",
+                    "IOE1",
+                    "ioe2",
+                    "IOE2",
+                    "bilou",
+                    "BILOU",
+                    "io",
+                    "IO",
+                    "nested_bioes",
+                    "NESTED_BIOES",
+                    "standoff",
+                    "conll",
+                    "conllu",
+                    "json",
+                    "jsonl",
+                    "brat",
+                    "doccano",
+                    "label_studio",
+                    "prodigy",
+                    "tei",
+                    "inline",
+                    "html",
+                    "mentions",
+                    "huggingface",
+                ]),
+        )
+        .arg(
+            Arg::with_name("inline_style")
+                .long("inline_style")
+                .takes_value(true)
+                .possible_values(&["xml", "markdown"])
+                .default_value("xml")
+                .value_name("STYLE")
+                .help("Sets the markup of the inline format: <CLASS>text</CLASS> (xml) or [text](CLASS) (markdown)"),
+        )
+        .arg(
+            Arg::with_name("inline_class")
+                .long("inline_class")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(3)
+                .value_names(&["CLASS", "OPEN", "CLOSE"])
+                .help("Sets the opening and closing markup of a class in the inline format ({class} is replaced by the class)"),
+        )
+        .arg(
+            Arg::with_name("tei_element")
+                .long("tei_element")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("CLASS:ELEMENT")
+                .help("Encodes a class as a TEI element in the tei format (defaults: PER:persName, LOC:placeName, ORG:orgName, others as rs)"),
+        )
+        .arg(
+            Arg::with_name("tei_insert")
+                .long("tei_insert")
+                .help("Reads inputs as TEI documents and annotates their text nodes (tei format)"),
+        )
+        .arg(
+            Arg::with_name("import")
+                .long("import")
+                .takes_value(true)
+                .possible_values(&[
+                    "doccano",
+                    "label_studio",
+                    "prodigy",
+                    "inline",
+                    "io",
+                    "iob1",
+                    "iob2",
+                    "ioe1",
+                    "ioe2",
+                    "bioes",
+                    "bilou",
+                ])
+                .value_name("TOOL")
+                .help("Reads inputs as annotations exported by a tool instead of tagging them (formats: iob, bioes, iob1, ioe1, ioe2, bilou, io, conll, conllu), or as tokens labeled in a scheme (one \"token label\" per line) converted to the scheme of the iob, bioes, iob1, ioe1, ioe2, bilou or io format"),
+        )
+        .arg(
+            Arg::with_name("output_dir")
+                .long("output_dir")
+                .takes_value(true)
+                .default_value(".")
+                .value_name("DIR")
+                .help("Sets the directory the brat format writes .txt and .ann files to, and the huggingface format writes label2id.json to"),
+        )
+        .arg(
+            Arg::with_name("scheme")
+                .long("scheme")
+                .takes_value(true)
+                .possible_values(&["io", "iob1", "iob2", "ioe1", "ioe2", "bioes", "bilou"])
+                .default_value("iob2")
+                .value_name("SCHEME")
+                .help("Sets the labeling scheme of the conll, conllu, json and jsonl formats"),
+        )
+        .arg(
+            Arg::with_name("conll_columns")
+                .long("conll_columns")
+                .takes_value(true)
+                .default_value("4")
+                .value_name("COUNT")
+                .help("Sets the number of columns of the conll format, token and label included"),
+        )
+        .arg(
+            Arg::with_name("conll_delimiter")
+                .long("conll_delimiter")
+                .takes_value(true)
+                .default_value("space")
+                .value_name("DELIMITER")
+                .help("Sets the column delimiter of the conll format (space, tab or any string)"),
+        )
+        .arg(
+            Arg::with_name("mentions_format")
+                .long("mentions_format")
+                .takes_value(true)
+                .possible_values(&["tsv", "csv"])
+                .default_value("tsv")
+                .value_name("FORMAT")
+                .help("Sets the field delimiter of the mentions format"),
+        )
+        .arg(
+            Arg::with_name("mentions_context")
+                .long("mentions_context")
+                .takes_value(true)
+                .default_value("0")
+                .value_name("CHARS")
+                .help("Adds the given number of characters around each mention to the mentions format"),
+        )
+        .arg(
+            Arg::with_name("max_tokens")
+                .long("max_tokens")
+                .takes_value(true)
+                .default_value("128")
+                .value_name("TOKENS")
+                .help("Splits sentences longer than the given number of tokens in the huggingface format"),
+        )
+        .arg(
+            Arg::with_name("tokenizer")
+                .long("tokenizer")
+                .takes_value(true)
+                .possible_values(&["whitespace", "punctuation", "unicode"])
+                .default_value("whitespace")
+                .value_name("TOKENIZER")
+                .help("Sets how tagged text is split into tokens (punctuation splits punctuation off words, as in CoNLL corpora)"),
+        )
+        .arg(
+            Arg::with_name("offsets")
+                .long("offsets")
+                .takes_value(true)
+                .possible_values(&["bytes", "chars", "utf16"])
+                .default_value("bytes")
+                .value_name("UNIT")
+                .help("Sets the unit of reported offsets (utf16 for JavaScript, chars for Python)"),
+        )
+        .arg(
+            Arg::with_name("span_end")
+                .long("span_end")
+                .takes_value(true)
+                .possible_values(&["inclusive", "exclusive"])
+                .default_value("inclusive")
+                .value_name("CONVENTION")
+                .help("Sets whether reported span ends are inclusive or exclusive"),
         )
         .arg(
             Arg::with_name("v")
@@ -76,7 +271,124 @@ pub fn cli() -> ArgMatches<'static> {
                         .possible_values(&["standard", "leftmostfirst", "leftmostlongest"])
                         .default_value("leftmostlongest")
                         .value_name("MATCH KIND")
-                        .help("Sets tagging matchkind"),
+                        .help("Sets tagging matchkind (standard keeps overlapping matches)"),
+                )
+                .arg(
+                    Arg::with_name("capitalization")
+                        .long("capitalization")
+                        .takes_value(true)
+                        .possible_values(&["any", "initial", "entry"])
+                        .value_name("CONSTRAINT")
+                        .help("Sets the capitalization constraint applied to matches unless the sentence is uppercase (dropped matches are reported by the standoff, json and jsonl formats)"),
+                )
+                .arg(
+                    Arg::with_name("backend")
+                        .long("backend")
+                        .takes_value(true)
+                        .possible_values(&BACKENDS)
+                        .default_value("aho-corasick")
+                        .value_name("BACKEND")
+                        .help("Sets the data structure used to match terms (bytes engine)"),
+                )
+                .arg(
+                    Arg::with_name("automaton_kind")
+                        .long("automaton_kind")
+                        .takes_value(true)
+                        .possible_values(&["auto", "nfa", "contiguous_nfa", "dfa"])
+                        .conflicts_with("backend")
+                        .value_name("KIND")
+                        .help("Sets the kind of Aho-Corasick automaton instead of --backend (auto picks one from the dictionary size)"),
+                )
+                .arg(prefilter_arg())
+                .arg(memory_limit_arg())
+                .arg(
+                    Arg::with_name("engine")
+                        .long("engine")
+                        .takes_value(true)
+                        .possible_values(&["bytes", "tokens"])
+                        .default_value("bytes")
+                        .value_name("ENGINE")
+                        .help("Sets the matching engine (tokens matches sequences of tokens instead of bytes, split as by the punctuation tokenizer)"),
+                )
+                .arg(
+                    Arg::with_name("fuzzy")
+                        .long("fuzzy")
+                        .takes_value(true)
+                        .possible_values(&["0", "1", "2"])
+                        .value_name("MAX DISTANCE")
+                        .help("Enables approximate matching with at most MAX DISTANCE edits per term (e.g. OCR errors)"),
+                )
+                .arg(
+                    Arg::with_name("stemming")
+                        .long("stemming")
+                        .takes_value(true)
+                        .possible_values(&["french", "english"])
+                        .value_name("LANGUAGE")
+                        .help("Enables matching of inflected forms with a Snowball stemmer"),
+                )
+                .arg(
+                    Arg::with_name("class_options")
+                        .long("class_options")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("CLASS:OPTIONS")
+                        .help("Sets matching options of a class (e.g. LOC:case_sensitive=false;capitalization=initial)"),
+                )
+                .arg(
+                    Arg::with_name("add")
+                        .long("add")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("FILE")
+                        .help("Adds the entries of a dictionary to the built tagger (TSV format)"),
+                )
+                .arg(
+                    Arg::with_name("remove")
+                        .long("remove")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("FILE")
+                        .help("Removes the terms of a file (one term per line) from the built tagger"),
+                )
+                .arg(
+                    Arg::with_name("max_segments")
+                        .long("max_segments")
+                        .takes_value(true)
+                        .value_name("COUNT")
+                        .help("Sets the number of segments (base and added entries) above which the tagger is compacted"),
+                )
+                .arg(
+                    Arg::with_name("compact")
+                        .long("compact")
+                        .help("Compacts the tagger once entries are added and removed"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("bench")
+                .about("compares matcher backends over the input files")
+                .arg(
+                    Arg::with_name("backends")
+                        .long("backends")
+                        .short("b")
+                        .takes_value(true)
+                        .multiple(true)
+                        .possible_values(&BACKENDS)
+                        .value_name("BACKEND")
+                        .help("Sets the backends to compare (all by default)"),
+                )
+                .arg(prefilter_arg())
+                .arg(memory_limit_arg())
+                .arg(
+                    Arg::with_name("case_sensitive")
+                        .long("case_sensitive")
+                        .short("c")
+                        .takes_value(true)
+                        .possible_values(&["true", "false"])
+                        .value_name("BOOL")
+                        .help("Enables/Disables the case sensitivity of the matchers"),
                 ),
         )
         .get_matches()
@@ -1,4 +1,13 @@
+mod options;
+mod storage;
+pub use options::*;
+pub use storage::*;
+
+use crate::errors::TooManySources;
+use crate::traits::HeapSize;
 use crate::types::*;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::ffi::OsStr;
 use std::fmt::Debug;
@@ -7,121 +16,405 @@ use std::io::prelude::*;
 use std::io::BufReader;
 use std::path::PathBuf;
 
-/// Represents a dictionary entry. A dictionary entry has a term and it's class.
-#[derive(Debug, Clone)]
+/// Represents a dictionary entry. A dictionary entry has a class and optional matching options.
+/// Its term and its identifier are stored in the string arenas of the dictionary, at the index of the entry.
+#[derive(Debug, Clone, Copy)]
 pub struct DictionaryEntry {
-    term: Term,
-    class: Class,
+    class: ClassId,
+    options: MatchOptions,
+    source: SourceIndex,
 }
 
 impl DictionaryEntry {
-    /// Returns the term of a dictionary entry
-    pub fn term(&self) -> &String {
-        &self.term
+    /// Returns the class id of a dictionary entry
+    #[allow(dead_code)]
+    pub fn class(&self) -> ClassId {
+        self.class
+    }
+
+    /// Returns the matching options of a dictionary entry
+    pub fn options(&self) -> MatchOptions {
+        self.options
+    }
+}
+
+/// Represents the dictionary file an entry comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionarySource {
+    path: PathBuf,
+    options: MatchOptions,
+    priority: Priority,
+    /// Position of the file among the files loaded together
+    rank: SourceIndex,
+}
+
+impl DictionarySource {
+    /// Returns the path of the dictionary file
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Returns the matching options set for the whole dictionary file
+    pub fn options(&self) -> MatchOptions {
+        self.options
     }
 
-    /// Returns the class of a dictionary entry
-    pub fn class(&self) -> &String {
-        &self.class
+    /// Returns the priority of the dictionary file. Entries of higher priority dictionaries win conflicts
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Returns the position of the dictionary file among the files loaded together (e.g. the order
+    /// of `-d` files). On equal priorities, entries of earlier files win conflicts
+    pub fn rank(&self) -> SourceIndex {
+        self.rank
     }
 }
 
 /// A struct used to build Dictionary
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DictionaryBuilder {
+    terms: StringArena,
+    identifiers: StringArena,
     entries: Entries,
+    classes: ClassTable,
+    sources: Vec<DictionarySource>,
 }
 
 impl DictionaryBuilder {
     pub fn from_files<P: Into<PathBuf> + AsRef<OsStr>>(
         paths: &[P],
     ) -> Result<DictionaryBuilder, Box<dyn Error>> {
-        let mut dictionaries = Vec::new();
-        let paths: Vec<PathBuf> = paths.into_iter().map(|p| p.into()).collect();
+        let mut builder = DictionaryBuilder::default();
+
+        let paths: Vec<PathBuf> = paths.iter().map(|p| p.into()).collect();
         for file in paths {
-            dictionaries.push(DictionaryBuilder::from_file(&file)?.build());
+            builder.load(file)?;
         }
 
-        let entries = dictionaries
-            .into_iter()
-            .flat_map(|dictionary| dictionary.entries().clone())
-            .collect::<Entries>();
-        Ok(DictionaryBuilder { entries: entries })
+        Ok(builder)
     }
-    /// Create a dictionnary from a TSV file (term, class)
+
+    /// Create a dictionnary from a TSV file (term, class, options, identifier).
+    ///
+    /// The options column is optional and holds `key=value` pairs separated by `;`.
+    /// The identifier column is optional and holds the identifier of the entity (e.g. an authority record).
+    /// Lines starting with `#!` hold options applied to the whole file, and the priority of the file (`priority=10`).
+    /// Files loaded together take precedence in loading order on equal priorities.
     pub fn from_file<P: Into<PathBuf>>(path: P) -> Result<DictionaryBuilder, Box<dyn Error>> {
+        let mut builder = DictionaryBuilder::default();
+        builder.load(path)?;
+
+        Ok(builder)
+    }
+
+    /// Loads the entries of a TSV file (see `from_file`)
+    fn load<P: Into<PathBuf>>(&mut self, path: P) -> Result<(), Box<dyn Error>> {
         let path = path.into();
         info!("Loading dictionary from file {:?}", path.display());
 
-        let dict_file = File::open(path)?;
-        let reader = BufReader::new(dict_file);
-
-        let terms = reader
-            .lines()
-            .into_iter()
-            .flat_map(|line| line)
-            .map(|line| {
-                let entry_elements = line.splitn(2, "\t").collect::<Vec<&str>>();
-
-                match (entry_elements.get(0), entry_elements.get(1)) {
-                    (Some(term), Some(category)) if term.len() > 7 => Some(DictionaryEntry {
-                        term: term.to_string(),
-                        class: category.to_string(),
-                    }),
-                    _ => None,
-                }
-            })
-            .flat_map(|entry| entry)
-            .collect::<Vec<DictionaryEntry>>();
+        let dict_file = File::open(&path)?;
+        self.read(path, BufReader::new(dict_file))?;
 
         info!("Dictionary loaded");
 
-        Ok(DictionaryBuilder { entries: terms })
+        Ok(())
+    }
+
+    /// Reads the entries of a TSV dictionary (see `from_file`). The path identifies the source of the entries
+    pub fn read<P: Into<PathBuf>, R: BufRead>(
+        &mut self,
+        path: P,
+        reader: R,
+    ) -> Result<(), Box<dyn Error>> {
+        let path = path.into();
+        let source = SourceIndex::try_from(self.sources.len())?;
+        let mut file_options = MatchOptions::default();
+        let mut priority = Priority::default();
+
+        for line in reader.lines() {
+            let line = line?;
+
+            if let Some(directive) = line.strip_prefix("#!") {
+                let (priorities, options): (Vec<&str>, Vec<&str>) = directive
+                    .split(';')
+                    .partition(|option| option.trim().starts_with("priority="));
+
+                if let Some(value) = priorities.last().and_then(|p| p.split_once('=')) {
+                    priority = value.1.trim().parse()?;
+                }
+
+                file_options = options.join(";").parse::<MatchOptions>()?.or(file_options);
+                continue;
+            }
+
+            let entry_elements = line.splitn(4, '\t').collect::<Vec<&str>>();
+
+            if let (Some(term), Some(category)) = (entry_elements.first(), entry_elements.get(1)) {
+                if !term.trim().is_empty() {
+                    let options = match entry_elements.get(2) {
+                        Some(options) => options.parse()?,
+                        None => MatchOptions::default(),
+                    };
+
+                    self.terms.push(term)?;
+                    self.identifiers
+                        .push(entry_elements.get(3).map_or("", |id| id.trim()))?;
+                    self.entries.push(DictionaryEntry {
+                        class: self.classes.intern(category)?,
+                        options,
+                        source,
+                    });
+                }
+            }
+        }
+
+        self.sources.push(DictionarySource {
+            path,
+            options: file_options,
+            priority,
+            rank: source,
+        });
+
+        Ok(())
+    }
+
+    /// Copies some entries of a dictionary, with the file options and the priority they come with
+    pub fn extend_from(
+        &mut self,
+        dict: &Dictionary,
+        indexes: &[DictionaryIndex],
+    ) -> Result<(), Box<dyn Error>> {
+        let mut sources: HashMap<SourceIndex, SourceIndex> = HashMap::new();
+
+        for index in indexes {
+            let entry = dict.entries[*index];
+
+            let source = match sources.get(&entry.source) {
+                Some(source) => *source,
+                None => {
+                    let source = dict.get_source(&entry);
+                    let position = match self.sources.iter().position(|s| s == source) {
+                        Some(position) => position,
+                        None => {
+                            self.sources.push(source.clone());
+                            self.sources.len() - 1
+                        }
+                    };
+
+                    let position = SourceIndex::try_from(position)?;
+                    sources.insert(entry.source, position);
+                    position
+                }
+            };
+
+            self.terms.push(dict.get_term(*index))?;
+            self.identifiers
+                .push(dict.get_identifier(*index).unwrap_or_default())?;
+            self.entries.push(DictionaryEntry {
+                class: self.classes.intern(dict.get_class(*index))?,
+                options: entry.options,
+                source,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Ranks the files of the builder after the given rank, as if they were loaded after it
+    pub fn rank_after(&mut self, rank: SourceIndex) -> Result<(), TooManySources> {
+        for source in &mut self.sources {
+            source.rank = rank
+                .checked_add(1)
+                .and_then(|next| next.checked_add(source.rank))
+                .ok_or(TooManySources(SourceIndex::MAX as usize + 1))?;
+        }
+
+        Ok(())
     }
 
-    pub fn build(self) -> Dictionary {
-        Dictionary {
-            entries: self.entries,
+    /// Builds the dictionary. When a term appears several times, only the entry
+    /// of the highest priority dictionary is kept (the first one on equal priorities)
+    pub fn build(self) -> Result<Dictionary, Box<dyn Error>> {
+        let mut kept: HashMap<&str, usize> = HashMap::new();
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let priority = self.sources[entry.source as usize].priority;
+
+            match kept.get(self.terms.get(index)) {
+                Some(kept_index)
+                    if self.sources[self.entries[*kept_index].source as usize].priority
+                        >= priority => {}
+                _ => {
+                    kept.insert(self.terms.get(index), index);
+                }
+            }
+        }
+
+        let mut kept = kept.into_values().collect::<Vec<usize>>();
+        kept.sort_unstable();
+
+        if kept.len() < self.entries.len() {
+            info!(
+                "{} duplicated terms overridden by dictionary precedence",
+                self.entries.len() - kept.len()
+            );
+        }
+
+        let mut terms = StringArena::default();
+        let mut identifiers = StringArena::default();
+        let mut entries = Entries::with_capacity(kept.len());
+
+        for index in kept {
+            terms.push(self.terms.get(index))?;
+            identifiers.push(self.identifiers.get(index))?;
+            entries.push(self.entries[index]);
         }
+
+        terms.shrink_to_fit();
+        identifiers.shrink_to_fit();
+
+        let dictionary = Dictionary {
+            terms,
+            identifiers,
+            entries,
+            classes: self.classes,
+            sources: self.sources,
+        };
+
+        dictionary.log_memory_usage();
+
+        Ok(dictionary)
     }
 }
 
-/// A struct representing a dictionary. A dictionnary is composed of DictionaryEntries
+/// A struct representing a dictionary. A dictionnary is composed of DictionaryEntries.
+/// Terms are stored in a single string arena and classes are interned.
 #[derive(Debug, Clone)]
 pub struct Dictionary {
+    terms: StringArena,
+    /// Identifiers of entries, empty when an entry has none
+    identifiers: StringArena,
     entries: Entries,
+    classes: ClassTable,
+    sources: Vec<DictionarySource>,
 }
 
 impl Dictionary {
     /// Returns all entries of a dictionary
-    fn entries(&self) -> &Vec<DictionaryEntry> {
+    pub fn entries(&self) -> &Vec<DictionaryEntry> {
         &self.entries
     }
 
-    /// Returns all terms of the dictionary
-    pub fn terms(&self) -> Terms {
-        let terms = self.entries().iter().map(|e| e.term()).collect::<Terms>();
+    /// Returns the number of entries of the dictionary
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
 
-        terms
+    /// Returns all terms of the dictionary, in entry order
+    pub fn terms(&self) -> Terms<'_> {
+        self.terms.iter().collect::<Terms>()
     }
 
     /// Returns uniq classes of the dictionary
-    #[allow(dead_code)]
-    fn classes(&self) -> Classes {
-        let mut classes = self
-            .entries()
-            .iter()
-            .map(|e| e.class())
-            .collect::<Classes>();
+    pub fn classes(&self) -> Classes<'_> {
+        let mut classes = self.classes.iter().collect::<Classes>();
 
         classes.sort();
-        classes.dedup();
 
         classes
     }
 
+    /// Get the term of an entry by it's index in the dictionary
+    pub fn get_term(&self, index: usize) -> &str {
+        self.terms.get(index)
+    }
+
+    /// Get the identifier of an entry by it's index in the dictionary
+    pub fn get_identifier(&self, index: usize) -> Option<&str> {
+        Some(self.identifiers.get(index)).filter(|identifier| !identifier.is_empty())
+    }
+
     /// Get the class of a term by it's index in the dictionary
-    pub fn get_class(&self, index: usize) -> String {
-        self.entries[index].class().to_string()
+    pub fn get_class(&self, index: usize) -> &str {
+        self.classes.name(self.entries[index].class)
+    }
+
+    /// Get the dictionary file an entry comes from
+    pub fn get_source(&self, entry: &DictionaryEntry) -> &DictionarySource {
+        &self.sources[entry.source as usize]
+    }
+
+    /// Returns the dictionary files of the entries
+    pub fn sources(&self) -> &[DictionarySource] {
+        &self.sources
+    }
+
+    /// Get the rank of the dictionary file of a term by it's index in the dictionary
+    pub fn get_rank(&self, index: usize) -> SourceIndex {
+        self.get_source(&self.entries[index]).rank()
+    }
+
+    /// Get the priority of a term by it's index in the dictionary
+    pub fn get_priority(&self, index: usize) -> Priority {
+        self.get_source(&self.entries[index]).priority()
+    }
+
+    /// Logs the memory used by the dictionary
+    fn log_memory_usage(&self) {
+        let megabytes = |bytes: usize| bytes as f64 / 1_000_000.0;
+        let entries_bytes = self.entries.capacity() * std::mem::size_of::<DictionaryEntry>();
+
+        info!(
+            "Dictionary built ({} entries, {} classes): {:.2} MB (terms {:.2} MB, entries {:.2} MB, classes {:.2} MB)",
+            self.len(),
+            self.classes.len(),
+            megabytes(self.heap_bytes()),
+            megabytes(self.terms.heap_bytes()),
+            megabytes(entries_bytes),
+            megabytes(self.classes.heap_bytes())
+        );
+    }
+}
+
+impl HeapSize for Dictionary {
+    fn heap_bytes(&self) -> usize {
+        let sources = self
+            .sources
+            .iter()
+            .map(|source| std::mem::size_of::<DictionarySource>() + source.path.capacity())
+            .sum::<usize>();
+
+        self.terms.heap_bytes()
+            + self.identifiers.heap_bytes()
+            + self.entries.capacity() * std::mem::size_of::<DictionaryEntry>()
+            + self.classes.heap_bytes()
+            + sources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreadable_lines_are_reported() {
+        let mut builder = DictionaryBuilder::default();
+        let content = b"Lyon\tLOC\n\xff\tLOC\nParis\tLOC\n";
+
+        assert!(builder.read("dict.tsv", &content[..]).is_err());
+    }
+
+    #[test]
+    fn ranks_are_bounded() {
+        let mut builder = DictionaryBuilder::default();
+        builder.read("first.tsv", &b"Lyon\tLOC\n"[..]).unwrap();
+        builder.read("second.tsv", &b"Paris\tLOC\n"[..]).unwrap();
+
+        builder.rank_after(10).unwrap();
+        let ranks = builder.sources.iter().map(|source| source.rank());
+        assert_eq!(ranks.collect::<Vec<SourceIndex>>(), [11, 12]);
+
+        assert!(builder.rank_after(SourceIndex::MAX - 12).is_err());
     }
 }
@@ -0,0 +1,121 @@
+use crate::errors::InvalidMatchOptions;
+use std::str::FromStr;
+
+/// Matching options attachable to a dictionary file, a class or a dictionary entry.
+/// Unset options fall back to the less specific level: entry > class > file > tagger
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MatchOptions {
+    pub case_sensitive: Option<bool>,
+    pub word_matching: Option<bool>,
+    pub capitalization: Option<Capitalization>,
+}
+
+impl MatchOptions {
+    /// Fills unset options with the options of a less specific level
+    pub fn or(self, other: MatchOptions) -> MatchOptions {
+        MatchOptions {
+            case_sensitive: self.case_sensitive.or(other.case_sensitive),
+            word_matching: self.word_matching.or(other.word_matching),
+            capitalization: self.capitalization.or(other.capitalization),
+        }
+    }
+}
+
+/// Parses options written as `key=value` pairs separated by `;` (e.g. `case_sensitive=true;word_matching=false`)
+impl FromStr for MatchOptions {
+    type Err = InvalidMatchOptions;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut options = MatchOptions::default();
+
+        for option in input.split(';').map(|o| o.trim()).filter(|o| !o.is_empty()) {
+            let (key, value) = match option.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(InvalidMatchOptions(option.to_string())),
+            };
+
+            let invalid = || InvalidMatchOptions(option.to_string());
+
+            match key {
+                "case_sensitive" => {
+                    options.case_sensitive = Some(value.parse().map_err(|_| invalid())?)
+                }
+                "word_matching" => {
+                    options.word_matching = Some(value.parse().map_err(|_| invalid())?)
+                }
+                "capitalization" => {
+                    options.capitalization = Some(value.parse().map_err(|_| invalid())?)
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Constrains the casing of a match to cut false positives caused by common words.
+/// Constraints are not applied when the whole sentence is uppercase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capitalization {
+    /// Any casing is accepted
+    Any,
+    /// The match must start with an uppercase letter
+    Initial,
+    /// Each word starting with an uppercase letter in the dictionary entry must start with an uppercase
+    /// letter in the text, and uppercase words (acronyms) must be uppercase in the text
+    Entry,
+}
+
+impl Capitalization {
+    /// Checks if the casing of a match is compatible with a dictionary term
+    pub fn accepts(&self, term: &str, matched_text: &str, sentence: &str) -> bool {
+        if *self == Capitalization::Any || is_uppercase(sentence) {
+            return true;
+        }
+
+        match self {
+            Capitalization::Any => true,
+            Capitalization::Initial => starts_with_uppercase(matched_text),
+            Capitalization::Entry => term
+                .split_whitespace()
+                .zip(matched_text.split_whitespace())
+                .all(|(term_word, word)| {
+                    if term_word.chars().filter(|c| c.is_alphabetic()).count() > 1
+                        && is_uppercase(term_word)
+                    {
+                        is_uppercase(word)
+                    } else if starts_with_uppercase(term_word) {
+                        starts_with_uppercase(word)
+                    } else {
+                        true
+                    }
+                }),
+        }
+    }
+}
+
+impl FromStr for Capitalization {
+    type Err = InvalidMatchOptions;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "any" => Ok(Capitalization::Any),
+            "initial" => Ok(Capitalization::Initial),
+            "entry" => Ok(Capitalization::Entry),
+            _ => Err(InvalidMatchOptions(input.to_string())),
+        }
+    }
+}
+
+/// Returns true if the first letter of a text is uppercase
+fn starts_with_uppercase(text: &str) -> bool {
+    text.chars()
+        .find(|c| c.is_alphabetic())
+        .is_some_and(|c| c.is_uppercase())
+}
+
+/// Returns true if a text has cased letters and all of them are uppercase
+fn is_uppercase(text: &str) -> bool {
+    text.chars().any(|c| c.is_uppercase()) && !text.chars().any(|c| c.is_lowercase())
+}
@@ -0,0 +1,150 @@
+use crate::errors::{StringArenaFull, TooManyClasses};
+use crate::traits::HeapSize;
+use crate::types::*;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::mem::size_of;
+
+/// Stores strings one after the other in a single buffer. A string is retrieved by its insertion index
+#[derive(Debug, Clone, Default)]
+pub struct StringArena {
+    buffer: String,
+    ends: Vec<u32>,
+}
+
+impl StringArena {
+    /// Appends a string to the arena and returns its index. The arena is limited to 4 GiB
+    pub fn push(&mut self, value: &str) -> Result<usize, StringArenaFull> {
+        let end = u32::try_from(self.buffer.len() + value.len()).map_err(|_| StringArenaFull)?;
+
+        self.buffer.push_str(value);
+        self.ends.push(end);
+
+        Ok(self.ends.len() - 1)
+    }
+
+    /// Returns the string stored at a given index
+    pub fn get(&self, index: usize) -> &str {
+        let start = match index {
+            0 => 0,
+            _ => self.ends[index - 1] as usize,
+        };
+
+        &self.buffer[start..self.ends[index] as usize]
+    }
+
+    /// Returns the number of strings in the arena
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Iterates over the strings of the arena, in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        (0..self.len()).map(move |index| self.get(index))
+    }
+
+    /// Releases unused capacity
+    pub fn shrink_to_fit(&mut self) {
+        self.buffer.shrink_to_fit();
+        self.ends.shrink_to_fit();
+    }
+}
+
+impl HeapSize for StringArena {
+    fn heap_bytes(&self) -> usize {
+        self.buffer.capacity() + self.ends.capacity() * size_of::<u32>()
+    }
+}
+
+/// Interns class names: each distinct class is stored once and referred to by its ClassId
+#[derive(Debug, Clone, Default)]
+pub struct ClassTable {
+    names: Vec<Class>,
+    ids: HashMap<Class, ClassId>,
+}
+
+impl ClassTable {
+    /// Returns the id of a class, registering the class if it's unknown
+    pub fn intern(&mut self, name: &str) -> Result<ClassId, TooManyClasses> {
+        if let Some(id) = self.ids.get(name) {
+            return Ok(*id);
+        }
+
+        let id = ClassId::try_from(self.names.len())
+            .map_err(|_| TooManyClasses(ClassId::MAX as usize + 1))?;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+
+        Ok(id)
+    }
+
+    /// Returns the name of a class
+    pub fn name(&self, id: ClassId) -> &str {
+        &self.names[id as usize]
+    }
+
+    /// Returns the id of a class, if the class is known
+    #[allow(dead_code)]
+    pub fn id(&self, name: &str) -> Option<ClassId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the number of distinct classes
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Iterates over class names, by id
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|name| name.as_str())
+    }
+}
+
+impl HeapSize for ClassTable {
+    fn heap_bytes(&self) -> usize {
+        let names = self.names.iter().map(|name| name.capacity()).sum::<usize>();
+
+        // Names are stored twice: by id and as keys of the lookup table
+        self.names.capacity() * size_of::<Class>()
+            + self.ids.capacity() * (size_of::<Class>() + size_of::<ClassId>())
+            + 2 * names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arena_strings_are_sliced_by_index() {
+        let mut arena = StringArena::default();
+
+        assert_eq!(arena.push("Lyon").unwrap(), 0);
+        assert_eq!(arena.push("").unwrap(), 1);
+        assert_eq!(arena.push("Université").unwrap(), 2);
+
+        assert_eq!(arena.get(0), "Lyon");
+        assert_eq!(arena.get(1), "");
+        assert_eq!(arena.get(2), "Université");
+        assert_eq!(
+            arena.iter().collect::<Vec<&str>>(),
+            ["Lyon", "", "Université"]
+        );
+    }
+
+    #[test]
+    fn classes_are_interned_once() {
+        let mut classes = ClassTable::default();
+
+        let loc = classes.intern("LOC").unwrap();
+        let org = classes.intern("ORG").unwrap();
+
+        assert_ne!(loc, org);
+        assert_eq!(classes.intern("LOC").unwrap(), loc);
+        assert_eq!(classes.len(), 2);
+
+        assert_eq!(classes.name(org), "ORG");
+        assert_eq!(classes.id("ORG"), Some(org));
+        assert_eq!(classes.id("PER"), None);
+    }
+}
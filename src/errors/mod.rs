@@ -12,3 +12,212 @@ impl fmt::Display for MissingDictionnary {
 }
 
 impl Error for MissingDictionnary {}
+
+/// This error is returned when matching options can't be parsed
+#[derive(Debug, Clone)]
+pub struct InvalidMatchOptions(pub String);
+
+impl fmt::Display for InvalidMatchOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid matching option: {}", self.0)
+    }
+}
+
+impl Error for InvalidMatchOptions {}
+
+/// This error is returned when a matcher backend is unknown
+#[derive(Debug, Clone)]
+pub struct InvalidBackend(pub String);
+
+impl fmt::Display for InvalidBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown matcher backend: {}", self.0)
+    }
+}
+
+impl Error for InvalidBackend {}
+
+/// This error is returned when an automaton can't fit in the memory limit
+#[derive(Debug, Clone)]
+pub struct MemoryLimitExceeded {
+    pub limit: usize,
+    pub usage: usize,
+}
+
+impl fmt::Display for MemoryLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The most compact automaton uses {} bytes, exceeding the memory limit of {} bytes",
+            self.usage, self.limit
+        )
+    }
+}
+
+impl Error for MemoryLimitExceeded {}
+
+/// This error is returned when a double-array automaton can't be built (e.g. too many patterns)
+#[derive(Debug, Clone)]
+pub struct DoubleArrayBuildError(pub String);
+
+impl fmt::Display for DoubleArrayBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Double-array automaton can't be built: {}", self.0)
+    }
+}
+
+impl Error for DoubleArrayBuildError {}
+
+/// This error is returned when an offset unit or an end convention is unknown
+#[derive(Debug, Clone)]
+pub struct InvalidOffsets(pub String);
+
+impl fmt::Display for InvalidOffsets {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown offset unit or end convention: {}", self.0)
+    }
+}
+
+impl Error for InvalidOffsets {}
+
+/// This error is returned when a tokenizer is unknown
+#[derive(Debug, Clone)]
+pub struct InvalidTokenizer(pub String);
+
+impl fmt::Display for InvalidTokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown tokenizer: {}", self.0)
+    }
+}
+
+impl Error for InvalidTokenizer {}
+
+/// This error is returned when a sequence-labeling scheme is unknown
+#[derive(Debug, Clone)]
+pub struct InvalidScheme(pub String);
+
+impl fmt::Display for InvalidScheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown labeling scheme: {}", self.0)
+    }
+}
+
+impl Error for InvalidScheme {}
+
+/// This error is returned when the annotations exported by an annotation tool can't be read
+#[derive(Debug, Clone)]
+pub struct InvalidAnnotation(pub String);
+
+impl fmt::Display for InvalidAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid annotation: {}", self.0)
+    }
+}
+
+impl Error for InvalidAnnotation {}
+
+/// This error is returned when an output format can't be used with imported annotations
+#[derive(Debug, Clone)]
+pub struct UnsupportedFormat(pub String);
+
+impl fmt::Display for UnsupportedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The {} format can't be used with imported annotations",
+            self.0
+        )
+    }
+}
+
+impl Error for UnsupportedFormat {}
+
+/// This error is returned when a label has no id in a label map
+#[derive(Debug, Clone)]
+pub struct UnknownLabel(pub String);
+
+impl fmt::Display for UnknownLabel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown label: {}", self.0)
+    }
+}
+
+impl Error for UnknownLabel {}
+
+/// This error is returned when a TEI element mapping isn't written as CLASS:ELEMENT
+#[derive(Debug, Clone)]
+pub struct InvalidTeiElement(pub String);
+
+impl fmt::Display for InvalidTeiElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid TEI element: {} (expected CLASS:ELEMENT)",
+            self.0
+        )
+    }
+}
+
+impl Error for InvalidTeiElement {}
+
+/// This error is returned when the maximum edit distance of approximate matching is too high
+#[derive(Debug, Clone)]
+pub struct InvalidEditDistance {
+    pub distance: u8,
+    pub max: u8,
+}
+
+impl fmt::Display for InvalidEditDistance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Approximate matching tolerates {} edits at most, not {}",
+            self.max, self.distance
+        )
+    }
+}
+
+impl Error for InvalidEditDistance {}
+
+/// This error is returned when the strings of a dictionary exceed the 4 GiB of a string arena
+#[derive(Debug, Clone)]
+pub struct StringArenaFull;
+
+impl fmt::Display for StringArenaFull {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The terms and identifiers of a dictionary are limited to 4 GiB"
+        )
+    }
+}
+
+impl Error for StringArenaFull {}
+
+/// This error is returned when a dictionary has more classes than class ids
+#[derive(Debug, Clone)]
+pub struct TooManyClasses(pub usize);
+
+impl fmt::Display for TooManyClasses {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "A dictionary can't have more than {} classes", self.0)
+    }
+}
+
+impl Error for TooManyClasses {}
+
+/// This error is returned when the dictionary files of a tagger can't be ranked anymore
+#[derive(Debug, Clone)]
+pub struct TooManySources(pub usize);
+
+impl fmt::Display for TooManySources {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A tagger can't rank more than {} dictionary files",
+            self.0
+        )
+    }
+}
+
+impl Error for TooManySources {}
@@ -0,0 +1,99 @@
+use crate::errors::InvalidAnnotation;
+use crate::tagger::{
+    EndConvention, OffsetConverter, OffsetUnit, Offsets, Tag, TaggedContent, Tags,
+};
+
+/// Character offsets with an exclusive end, as used by annotation tools
+pub const TOOL_OFFSETS: Offsets = Offsets {
+    unit: OffsetUnit::Chars,
+    end: EndConvention::Exclusive,
+};
+
+/// A span given by character offsets (exclusive end) and its label
+pub type CharSpan = (usize, usize, String);
+
+/// A text and its tagged spans, as read from the export of an annotation tool
+#[derive(Debug, Clone)]
+pub struct AnnotatedText {
+    pub text: String,
+    spans: Vec<TaggedContent<'static>>,
+}
+
+impl AnnotatedText {
+    /// Builds an annotated text from sorted, non overlapping spans given by byte offsets
+    pub fn new(text: String, spans: Vec<TaggedContent<'static>>) -> AnnotatedText {
+        AnnotatedText { text, spans }
+    }
+
+    /// Builds an annotated text from spans given by character offsets (exclusive end).
+    /// Spans overlapping a previous (or longer) span are dropped, as tags can't overlap
+    pub fn from_char_spans(
+        text: String,
+        mut spans: Vec<CharSpan>,
+    ) -> Result<AnnotatedText, InvalidAnnotation> {
+        let converter = OffsetConverter::new(&text, TOOL_OFFSETS);
+        let mut tagged: Vec<TaggedContent<'static>> = vec![];
+
+        spans.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+        for (start, end, label) in spans {
+            let bytes = converter.byte(start).zip(converter.byte(end));
+
+            let (start, end) = match bytes {
+                Some((start, end)) if start < end => (start, end),
+                _ => {
+                    return Err(InvalidAnnotation(format!(
+                        "span {}..{} ({}) is out of {:?}",
+                        start, end, label, text
+                    )))
+                }
+            };
+
+            match tagged.last() {
+                Some(last) if start <= last.end => warn!(
+                    "Dropping {} span {:?}: it overlaps {} span {:?}",
+                    label,
+                    &text[start..end],
+                    last.class,
+                    last.original_text
+                ),
+                _ => tagged.push(TaggedContent::new(
+                    text[start..end].to_string(),
+                    start,
+                    end - 1,
+                    label,
+                )),
+            }
+        }
+
+        Ok(AnnotatedText {
+            text,
+            spans: tagged,
+        })
+    }
+
+    /// Returns the tags of the text
+    pub fn tags(&self) -> Tags<'_> {
+        Tags::from_spans(&self.text, &self.spans)
+    }
+}
+
+/// Returns the tagged spans of a text with their character offsets (exclusive end)
+pub fn char_spans<'t, 'a>(
+    text: &str,
+    tags: &'t Tags<'a>,
+) -> Vec<(usize, usize, &'t TaggedContent<'a>)> {
+    let converter = OffsetConverter::new(text, TOOL_OFFSETS);
+
+    tags.0
+        .iter()
+        .filter_map(|tag| match tag {
+            Tag::Tagged(span) => Some(span),
+            Tag::UnTagged(_) => None,
+        })
+        .map(|span| {
+            let (start, end) = converter.span(span.start, span.end);
+            (start, end, span)
+        })
+        .collect()
+}
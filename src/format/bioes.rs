@@ -2,25 +2,28 @@
 
 use colored::Colorize;
 
+use crate::format::WhitespaceTokenizer;
 use crate::tagger::Tags;
 use crate::tagger::{Tag, TaggedContent, UntaggedContent};
-use crate::traits::PrettyDisplay;
+use crate::traits::{PrettyDisplay, Tokenizer};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// Beginning Inside Outside End Single tagging format
-pub struct BIOES {
-    tags: Vec<BIOESTag>,
+pub struct BIOES<'a> {
+    tags: Vec<BIOESTag<'a>>,
 }
 
 /// Tags of the BIOES format
-#[derive(Debug, Clone)]
-pub enum BIOESTag {
-    Beginning(TaggedContent),
-    Inside(TaggedContent),
-    Outside(UntaggedContent),
-    End(TaggedContent),
-    Single(TaggedContent),
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BIOESTag<'a> {
+    Beginning(TaggedContent<'a>),
+    Inside(TaggedContent<'a>),
+    Outside(UntaggedContent<'a>),
+    End(TaggedContent<'a>),
+    Single(TaggedContent<'a>),
 }
 
 /// Creates a BIOES beginning tag
@@ -83,7 +86,41 @@ macro_rules! bioes_s {
     };
 }
 
-impl<'a> PrettyDisplay for BIOESTag {
+impl BIOESTag<'_> {
+    /// Returns the label of a tag (e.g. `B-LOC`, `O`)
+    pub fn label(&self) -> String {
+        match self {
+            Self::Beginning(tag) => format!("B-{}", tag.class),
+            Self::Inside(tag) => format!("I-{}", tag.class),
+            Self::Outside(_) => "O".to_string(),
+            Self::End(tag) => format!("E-{}", tag.class),
+            Self::Single(tag) => format!("S-{}", tag.class),
+        }
+    }
+
+    /// Returns the text of a tag
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Beginning(tag) | Self::Inside(tag) | Self::End(tag) | Self::Single(tag) => {
+                &tag.original_text
+            }
+            Self::Outside(tag) => &tag.original_text,
+        }
+    }
+
+    /// Returns the position of a tag in the text: first byte and last byte (inclusive end)
+    #[allow(dead_code)]
+    pub fn span(&self) -> (StartByte, EndByte) {
+        match self {
+            Self::Beginning(tag) | Self::Inside(tag) | Self::End(tag) | Self::Single(tag) => {
+                (tag.start, tag.end)
+            }
+            Self::Outside(tag) => (tag.start, tag.end),
+        }
+    }
+}
+
+impl PrettyDisplay for BIOESTag<'_> {
     fn pretty_display(&self) -> String {
         match self {
             Self::Beginning(tag) => format!(
@@ -124,34 +161,67 @@ impl<'a> PrettyDisplay for BIOESTag {
     }
 }
 
-impl<'a> fmt::Display for BIOESTag {
+impl fmt::Display for BIOESTag<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Beginning(tag) => write!(f, "{} {}{}", tag.original_text.trim(), "B-", tag.class),
-            Self::Inside(tag) => write!(f, "{} {}{}", tag.original_text.trim(), "I-", tag.class),
+            Self::Beginning(tag) => write!(f, "{} B-{}", tag.original_text.trim(), tag.class),
+            Self::Inside(tag) => write!(f, "{} I-{}", tag.original_text.trim(), tag.class),
             Self::Outside(tag) => write!(f, "{} {}", tag.original_text.trim(), "O".dimmed()),
-            Self::End(tag) => write!(f, "{} {}{}", tag.original_text.trim(), "E-", tag.class),
-            Self::Single(tag) => write!(f, "{} {}{}", tag.original_text.trim(), "S-", tag.class),
+            Self::End(tag) => write!(f, "{} E-{}", tag.original_text.trim(), tag.class),
+            Self::Single(tag) => write!(f, "{} S-{}", tag.original_text.trim(), tag.class),
         }
     }
 }
 
-impl<'a> From<Tags> for BIOES {
-    fn from(tags: Tags) -> Self {
-        let mut tgs: Vec<BIOESTag> = vec![];
+impl<'a> BIOES<'a> {
+    pub fn new(tags: Vec<BIOESTag<'a>>) -> BIOES<'a> {
+        BIOES { tags }
+    }
+
+    /// Returns the tags of the BIOES sequence
+    pub fn tags(&self) -> &[BIOESTag<'a>] {
+        &self.tags
+    }
+}
+
+impl<'a> BIOES<'a> {
+    /// Encodes tags in BIOES. Each tag is split into tokens by the given tokenizer
+    pub fn from_tags(tags: Tags<'a>, tokenizer: &dyn Tokenizer) -> BIOES<'a> {
+        let mut bioes_tags: Vec<BIOESTag> = vec![];
 
         for tag in tags.0 {
             match tag {
-                Tag::UnTagged(t) => tgs.append(&mut t.into()),
-                Tag::Tagged(t) => tgs.append(&mut t.into()),
+                Tag::Tagged(t) => {
+                    let tokens = t.tokens(tokenizer);
+                    let last = tokens.len().saturating_sub(1);
+
+                    for (position, token) in tokens.into_iter().enumerate() {
+                        match position {
+                            _ if last == 0 => bioes_tags.push(bioes_s!(token)),
+                            0 => bioes_tags.push(bioes_b!(token)),
+                            p if p == last => bioes_tags.push(bioes_e!(token)),
+                            _ => bioes_tags.push(bioes_i!(token)),
+                        }
+                    }
+                }
+                Tag::UnTagged(t) => {
+                    bioes_tags.extend(t.tokens(tokenizer).into_iter().map(|token| bioes_o!(token)))
+                }
             }
         }
 
-        BIOES { tags: tgs }
+        BIOES { tags: bioes_tags }
+    }
+}
+
+/// Encodes tags in BIOES, tokens are separated by whitespaces
+impl<'a> From<Tags<'a>> for BIOES<'a> {
+    fn from(tags: Tags<'a>) -> Self {
+        BIOES::from_tags(tags, &WhitespaceTokenizer)
     }
 }
 
-impl<'a> fmt::Display for BIOES {
+impl fmt::Display for BIOES<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let results = self
             .tags
@@ -162,87 +232,62 @@ impl<'a> fmt::Display for BIOES {
     }
 }
 
-impl<'a> PrettyDisplay for BIOES {
+impl PrettyDisplay for BIOES<'_> {
     fn pretty_display(&self) -> String {
         let results = self
             .tags
             .iter()
-            .map(|t| format!("{}", t.pretty_display()))
+            .map(|t| t.pretty_display().to_string())
             .collect::<Vec<_>>();
-        format!("{}", results.join("\n"))
+        results.join("\n").to_string()
     }
 }
 
-impl From<TaggedContent> for Vec<BIOESTag> {
-    fn from(tag: TaggedContent) -> Self {
-        let mut sub_tags = tag
-            .original_text
-            .split_terminator(|c: char| c.is_ascii_whitespace())
-            .collect::<Vec<&str>>()
-            .into_iter()
-            .peekable();
-
-        let mut bioes_tags = vec![];
-        let mut last_pos = tag.start;
-
-        // Beginning tag
-        while let Some(sub_tag) = sub_tags.next() {
-            if !sub_tag.is_empty() {
-                if sub_tags.peek().is_some() {
-                    bioes_tags.push(bioes_b![sub_tag, [last_pos => sub_tag.len()], &tag.class])
-                } else {
-                    bioes_tags.push(bioes_s![sub_tag, [last_pos => sub_tags.len()], &tag.class])
-                }
-
-                last_pos += sub_tag.len();
-                break;
-            }
-
-            last_pos += 1;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::fixtures::{tags, tokenizers, TEXT};
+    use crate::format::PunctuationTokenizer;
 
-        // Inside/End Tag
-        while let Some(sub_tag) = sub_tags.next() {
-            if !sub_tag.is_empty() {
-                if sub_tags.peek().is_some() {
-                    bioes_tags.push(bioes_i![sub_tag, [last_pos => sub_tag.len()], &tag.class])
-                } else {
-                    bioes_tags.push(bioes_e![sub_tag, [last_pos => sub_tag.len()], &tag.class])
-                }
+    #[test]
+    fn token_offsets_slice_the_input() {
+        for tokenizer in tokenizers().iter() {
+            let bioes = BIOES::from_tags(tags(), *tokenizer);
 
-                last_pos += sub_tag.len();
-            } else {
-                last_pos += 1;
+            for tag in bioes.tags() {
+                let (start, end) = tag.span();
+                assert_eq!(&TEXT[start..=end], tag.text());
             }
         }
-
-        bioes_tags
     }
-}
-
-impl From<UntaggedContent> for Vec<BIOESTag> {
-    fn from(tag: UntaggedContent) -> Self {
-        let mut sub_tags = tag
-            .original_text
-            .split_terminator(|c: char| c.is_ascii_whitespace())
-            .collect::<Vec<&str>>()
-            .into_iter()
-            .peekable();
-
-        let mut bioes_tags = vec![];
-        let mut last_pos = tag.start;
 
-        // Inside/End Tag
-        while let Some(sub_tag) = sub_tags.next() {
-            if !sub_tag.is_empty() {
-                bioes_tags.push(bioes_o![sub_tag, [last_pos => sub_tag.len()]]);
-
-                last_pos += sub_tag.len();
-            } else {
-                last_pos += 1;
-            }
-        }
+    #[test]
+    fn multi_token_entities_are_split() {
+        let bioes = BIOES::from_tags(tags(), &PunctuationTokenizer);
+        let labels = bioes
+            .tags()
+            .iter()
+            .map(|tag| format!("{} {}", tag.text(), tag.label()))
+            .collect::<Vec<String>>();
 
-        bioes_tags
+        assert_eq!(
+            labels,
+            [
+                "Né O",
+                "à O",
+                "Pierre-Bénite S-LOC",
+                ", O",
+                "près O",
+                "de O",
+                "l O",
+                "' O",
+                "Université B-LOC",
+                "de I-LOC",
+                "Lyon E-LOC",
+                "à O",
+                "7h O",
+                ". O"
+            ]
+        );
     }
 }
@@ -0,0 +1,175 @@
+use crate::tagger::{
+    EndConvention, NestedTags, OffsetConverter, OffsetUnit, Offsets, TaggedContent,
+};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+/// brat standoff format: the annotations of a document (`.ann` file), one `T` line per span
+/// (`T1\tCLASS start end\ttext`). Offsets are character offsets with an exclusive end, as brat
+/// requires. Spans may be nested or overlapping, never discontinuous.
+/// Whitespaces of classes are replaced by underscores, brat types being single words, and line
+/// breaks of span texts by spaces, as brat does.
+/// Approximate matches are followed by an `AnnotatorNotes` line holding the dictionary term
+pub struct Brat<'a> {
+    text: Cow<'a, str>,
+    spans: Vec<TaggedContent<'a>>,
+}
+
+impl<'a> From<NestedTags<'a>> for Brat<'a> {
+    fn from(nested_tags: NestedTags<'a>) -> Self {
+        Brat {
+            spans: nested_tags.spans().into_iter().cloned().collect(),
+            text: nested_tags.text,
+        }
+    }
+}
+
+impl Brat<'_> {
+    /// Writes the text (`.txt` file) and the annotations (`.ann` file) of a document in a directory.
+    /// Both files are named after the document, whose last extension is replaced (`report.v2.txt`
+    /// gives `report.v2.ann`). `names` holds the names already written during the run: a document
+    /// named like a previous one (e.g. in another input directory) gets a numbered suffix
+    /// (`report-2.ann`). Returns the path of the annotation file
+    pub fn write<P: AsRef<Path>>(
+        &self,
+        directory: P,
+        document: &Path,
+        names: &mut HashSet<OsString>,
+    ) -> io::Result<PathBuf> {
+        let file_name = Path::new(document.file_name().unwrap_or(document.as_os_str()));
+        let stem = file_name.with_extension("").into_os_string();
+
+        let mut name = stem.clone();
+        for suffix in 2.. {
+            if names.insert(name.clone()) {
+                break;
+            }
+
+            name = stem.clone();
+            name.push(format!("-{}", suffix));
+        }
+
+        let mut path = directory.as_ref().join(name);
+        fs::create_dir_all(directory.as_ref())?;
+
+        path.as_mut_os_string().push(".txt");
+        fs::write(&path, self.text.as_bytes())?;
+
+        let path = path.with_extension("ann");
+        fs::write(&path, self.to_string())?;
+
+        Ok(path)
+    }
+}
+
+impl fmt::Display for Brat<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offsets = Offsets {
+            unit: OffsetUnit::Chars,
+            end: EndConvention::Exclusive,
+        };
+        let converter = OffsetConverter::new(&self.text, offsets);
+        let mut notes = 0;
+
+        for (index, span) in self.spans.iter().enumerate() {
+            let (start, end) = converter.span(span.start, span.end);
+
+            writeln!(
+                f,
+                "T{}\t{} {} {}\t{}",
+                index + 1,
+                span.class.replace(char::is_whitespace, "_"),
+                start,
+                end,
+                span.original_text.replace(['\r', '\n'], " ")
+            )?;
+
+            if let Some(fuzzy) = &span.fuzzy {
+                notes += 1;
+
+                writeln!(
+                    f,
+                    "#{}\tAnnotatorNotes T{}\t≈ {} ({})",
+                    notes,
+                    index + 1,
+                    fuzzy.term,
+                    fuzzy.distance
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_are_exclusive_character_offsets() {
+        let text = "À Saint-Étienne";
+        let spans = vec![
+            TaggedContent::new("Saint-Étienne", 3, 16, "LOC"),
+            TaggedContent::new("Étienne", 9, 16, "PER").with_fuzzy_match("Etienne", 1),
+        ];
+
+        assert_eq!(
+            Brat::from(NestedTags::new(text, spans)).to_string(),
+            "T1\tLOC 2 15\tSaint-Étienne\nT2\tPER 8 15\tÉtienne\n#1\tAnnotatorNotes T2\t≈ Etienne (1)\n"
+        );
+    }
+
+    #[test]
+    fn line_breaks_of_spans_are_spaces() {
+        let text = "Université\r\nde Lyon";
+        let spans = vec![TaggedContent::new(text, 0, text.len() - 1, "ORG")];
+
+        assert_eq!(
+            Brat::from(NestedTags::new(text, spans)).to_string(),
+            "T1\tORG 0 19\tUniversité  de Lyon\n"
+        );
+    }
+
+    #[test]
+    fn documents_are_named_after_their_file_name() {
+        let directory =
+            std::env::temp_dir().join(format!("aho-nnotation-brat-{}", std::process::id()));
+        let brat = Brat::from(NestedTags::new("Lyon", vec![]));
+        let mut names = HashSet::new();
+
+        let documents = [
+            "a/report.v2.txt",
+            "a/report.v3.txt",
+            "b/report.v2.txt",
+            "notes",
+        ];
+        let written = documents
+            .iter()
+            .map(|document| brat.write(&directory, Path::new(document), &mut names))
+            .map(|path| path.unwrap().file_name().unwrap().to_os_string())
+            .collect::<Vec<OsString>>();
+
+        assert_eq!(
+            written,
+            [
+                "report.v2.ann",
+                "report.v3.ann",
+                "report.v2-2.ann",
+                "notes.ann"
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(directory.join("report.v2-2.txt")).unwrap(),
+            "Lyon"
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
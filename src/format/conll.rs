@@ -0,0 +1,204 @@
+use crate::format::SchemeTags;
+use std::borrow::Cow;
+use std::fmt;
+
+/// A tagged sentence and its text
+#[derive(Debug, Clone)]
+pub struct Sentence<'a> {
+    pub text: Cow<'a, str>,
+    pub tags: SchemeTags<'a>,
+}
+
+impl<'a> Sentence<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(text: S, tags: SchemeTags<'a>) -> Sentence<'a> {
+        Sentence {
+            text: text.into(),
+            tags,
+        }
+    }
+}
+
+/// Options of the CoNLL-2003 format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConllOptions {
+    /// Number of columns, token and label included. Columns in between are filled with `_`
+    pub columns: usize,
+    /// Separates the columns of a line
+    pub delimiter: String,
+}
+
+impl Default for ConllOptions {
+    fn default() -> Self {
+        ConllOptions {
+            columns: 4,
+            delimiter: " ".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// CoNLL-2003 format: a document starts with a `-DOCSTART-` line, followed by one line per token
+/// (token first, label last). Sentences are separated by a blank line
+pub struct Conll<'a> {
+    sentences: Vec<Sentence<'a>>,
+    options: ConllOptions,
+}
+
+impl<'a> Conll<'a> {
+    pub fn new(sentences: Vec<Sentence<'a>>) -> Conll<'a> {
+        Conll {
+            sentences,
+            options: ConllOptions::default(),
+        }
+    }
+
+    /// Sets the number of columns and the column delimiter
+    pub fn with_options(mut self, options: ConllOptions) -> Conll<'a> {
+        self.options = options;
+        self
+    }
+
+    /// Joins the token, the filler columns and the label of a line
+    fn line(&self, token: &str, filler: &str, label: &str) -> String {
+        let fillers = self.options.columns.saturating_sub(2);
+
+        std::iter::once(token)
+            .chain(std::iter::repeat_n(filler, fillers))
+            .chain(std::iter::once(label))
+            .collect::<Vec<&str>>()
+            .join(&self.options.delimiter)
+    }
+}
+
+impl fmt::Display for Conll<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.line("-DOCSTART-", "-X-", "O"))?;
+        writeln!(f)?;
+
+        for sentence in self.sentences.iter().filter(|s| !s.tags.tags().is_empty()) {
+            for tag in sentence.tags.tags() {
+                writeln!(f, "{}", self.line(tag.text(), "_", &tag.label()))?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// CoNLL-U format: ten tab separated columns per token, labels are written in the MISC column
+/// as `NER=<label>`. Each sentence is preceded by its id (`<document>-<n>`, unique across documents)
+/// and its text
+pub struct ConllU<'a> {
+    document: Cow<'a, str>,
+    sentences: Vec<Sentence<'a>>,
+}
+
+impl<'a> ConllU<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(document: S, sentences: Vec<Sentence<'a>>) -> ConllU<'a> {
+        ConllU {
+            document: document.into(),
+            sentences,
+        }
+    }
+}
+
+impl fmt::Display for ConllU<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# newdoc id = {}", self.document)?;
+
+        let sentences = self.sentences.iter().filter(|s| !s.tags.tags().is_empty());
+
+        for (sentence_id, sentence) in sentences.enumerate() {
+            writeln!(f, "# sent_id = {}-{}", self.document, sentence_id + 1)?;
+            writeln!(f, "# text = {}", sentence.text.trim())?;
+
+            let tags = sentence.tags.tags();
+
+            for (index, tag) in tags.iter().enumerate() {
+                let mut misc = format!("NER={}", tag.label());
+
+                if let Some(next) = tags.get(index + 1) {
+                    if next.span().0 == tag.span().1 + 1 {
+                        misc.push_str("|SpaceAfter=No");
+                    }
+                }
+
+                writeln!(
+                    f,
+                    "{}\t{}\t_\t_\t_\t_\t_\t_\t_\t{}",
+                    index + 1,
+                    tag.text(),
+                    misc
+                )?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::{PunctuationTokenizer, Scheme};
+    use crate::tagger::{TaggedContent, Tags};
+
+    const TEXT: &str = "Né à Lyon, en 1900.";
+
+    fn sentences() -> Vec<Sentence<'static>> {
+        let start = TEXT.find("Lyon").unwrap();
+        let spans = [TaggedContent::new("Lyon", start, start + 3, "LOC")];
+        let tags = SchemeTags::from_tags(
+            Tags::from_spans(TEXT, &spans),
+            Scheme::BILOU,
+            &PunctuationTokenizer,
+        );
+
+        vec![
+            Sentence::new(TEXT, tags),
+            Sentence::new(
+                "",
+                SchemeTags::from((Tags::from_spans("", &[]), Scheme::BILOU)),
+            ),
+        ]
+    }
+
+    #[test]
+    fn conll_lines_have_the_configured_columns() {
+        let conll = Conll::new(sentences()).with_options(ConllOptions {
+            columns: 3,
+            delimiter: "\t".to_string(),
+        });
+
+        assert_eq!(
+            conll.to_string(),
+            "-DOCSTART-\t-X-\tO\n\nNé\t_\tO\nà\t_\tO\nLyon\t_\tU-LOC\n,\t_\tO\nen\t_\tO\n1900\t_\tO\n.\t_\tO\n\n"
+        );
+    }
+
+    #[test]
+    fn conllu_labels_are_in_misc_column() {
+        let conllu = ConllU::new("doc", sentences()).to_string();
+        let lines = conllu.lines().collect::<Vec<&str>>();
+
+        assert_eq!(
+            lines[..3],
+            [
+                "# newdoc id = doc",
+                "# sent_id = doc-1",
+                "# text = Né à Lyon, en 1900."
+            ]
+        );
+        assert_eq!(
+            lines[5],
+            "3\tLyon\t_\t_\t_\t_\t_\t_\t_\tNER=U-LOC|SpaceAfter=No"
+        );
+        assert_eq!(lines[9], "7\t.\t_\t_\t_\t_\t_\t_\t_\tNER=O");
+        assert_eq!(lines.len(), 11);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::format::{char_spans, AnnotatedText};
+use crate::tagger::Tags;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Doccano sequence labeling record (JSONL): `{"text": ..., "label": [[start, end, "CLASS"]]}`.
+/// Offsets are character offsets with an exclusive end.
+/// Exports using `labels` or `entities` (`start_offset`, `end_offset`, `label`) are read as well
+pub struct Doccano<'a> {
+    text: Cow<'a, str>,
+    #[serde(default, alias = "labels")]
+    label: Vec<(usize, usize, Cow<'a, str>)>,
+    #[serde(default, skip_serializing)]
+    entities: Vec<DoccanoEntity>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// A span of the Doccano exports listing entities
+pub struct DoccanoEntity {
+    start_offset: usize,
+    end_offset: usize,
+    label: String,
+}
+
+impl<'a> Doccano<'a> {
+    /// Builds the Doccano record of a tagged text
+    pub fn from_tags(text: &'a str, tags: &Tags<'a>) -> Doccano<'a> {
+        let label = char_spans(text, tags)
+            .into_iter()
+            .map(|(start, end, span)| (start, end, span.class.clone()))
+            .collect();
+
+        Doccano {
+            text: Cow::Borrowed(text),
+            label,
+            entities: vec![],
+        }
+    }
+
+    /// Reads the records of a Doccano JSONL export
+    pub fn read(input: &str) -> Result<Vec<AnnotatedText>, Box<dyn Error>> {
+        let mut texts = vec![];
+
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            let record: Doccano = serde_json::from_str(line)?;
+
+            let spans = record
+                .label
+                .into_iter()
+                .map(|(start, end, label)| (start, end, label.into_owned()))
+                .chain(
+                    record
+                        .entities
+                        .into_iter()
+                        .map(|entity| (entity.start_offset, entity.end_offset, entity.label)),
+                )
+                .collect();
+
+            texts.push(AnnotatedText::from_char_spans(
+                record.text.into_owned(),
+                spans,
+            )?);
+        }
+
+        Ok(texts)
+    }
+}
+
+/// Prints the record on a single line
+impl fmt::Display for Doccano<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::{Tag, TaggedContent};
+
+    #[test]
+    fn records_are_read_back_into_tags() {
+        let text = "À Saint-Étienne";
+        let spans = [TaggedContent::new("Saint-Étienne", 3, 16, "LOC")];
+        let tags = Tags::from_spans(text, &spans);
+
+        let record = Doccano::from_tags(text, &tags).to_string();
+        assert_eq!(
+            record,
+            r#"{"text":"À Saint-Étienne","label":[[2,15,"LOC"]]}"#
+        );
+
+        let entities = r#"{"text":"À Saint-Étienne","entities":[{"id":1,"label":"LOC","start_offset":2,"end_offset":15}]}"#;
+
+        for input in [record.as_str(), entities].iter() {
+            let texts = Doccano::read(input).unwrap();
+            let spans = texts[0].tags().0;
+
+            assert_eq!(spans.len(), 2);
+            match &spans[1] {
+                Tag::Tagged(span) => assert_eq!((span.start, span.end), (3, 16)),
+                Tag::UnTagged(_) => panic!("Saint-Étienne isn't tagged"),
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::format::{PunctuationTokenizer, UnicodeTokenizer, WhitespaceTokenizer};
+use crate::tagger::{TaggedContent, Tags};
+use crate::traits::Tokenizer;
+
+/// Text mixing multi-byte characters, a double space, a non-breaking space, a tab and punctuation
+pub const TEXT: &str = "Né à  Pierre-Bénite,\u{a0}près de l'Université de Lyon\tà 7h.";
+
+/// Tags of `TEXT`: a single-word and a multi-word location
+pub fn tags() -> Tags<'static> {
+    let spans = ["Pierre-Bénite", "Université de Lyon"]
+        .iter()
+        .map(|term| {
+            let start = TEXT.find(term).unwrap();
+            let end = start + term.len() - 1;
+            TaggedContent::new(&TEXT[start..=end], start, end, "LOC")
+        })
+        .collect::<Vec<TaggedContent>>();
+
+    Tags::from_spans(TEXT, &spans)
+}
+
+/// Every built-in tokenizer
+pub fn tokenizers() -> [&'static dyn Tokenizer; 3] {
+    [
+        &WhitespaceTokenizer,
+        &PunctuationTokenizer,
+        &UnicodeTokenizer,
+    ]
+}
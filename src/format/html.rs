@@ -0,0 +1,218 @@
+use crate::tagger::{Tag, Tags};
+use quick_xml::escape::escape;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Background colors of classes, assigned by class name order
+const PALETTE: [&str; 12] = [
+    "#ffd8a8", "#b2f2bb", "#a5d8ff", "#eebefa", "#ffec99", "#ffc9c9", "#99e9f2", "#d0bfff",
+    "#c0eb75", "#fcc2d7", "#96f2d7", "#bac8ff",
+];
+
+const STYLE: &str =
+    "body{font-family:sans-serif;max-width:60em;margin:auto;padding:1em;line-height:1.6}\
+mark{border-radius:.2em;padding:0 .15em;cursor:help}\
+.legend span{display:inline-block;margin:.2em .5em .2em 0}\
+section{border-top:1px solid #ccc;margin-top:1.5em}";
+
+/// A tagged document of an HTML report
+#[derive(Debug, Clone)]
+struct HtmlDocument {
+    title: String,
+    paragraphs: Vec<String>,
+    entities: usize,
+}
+
+/// Self-contained HTML report: documents with highlighted spans (one color per class), tooltips
+/// holding the class, the identifier and the dictionary of spans, a class legend with counts
+/// and a table of contents. The report holds no external resource
+#[derive(Debug, Clone, Default)]
+pub struct HtmlReport {
+    documents: Vec<HtmlDocument>,
+    counts: BTreeMap<String, usize>,
+}
+
+impl HtmlReport {
+    /// Starts a new document. Tags are added to the last started document
+    pub fn document<S: Into<String>>(&mut self, title: S) {
+        self.documents.push(HtmlDocument {
+            title: title.into(),
+            paragraphs: vec![],
+            entities: 0,
+        });
+    }
+
+    /// Adds a paragraph holding the tagged text to the current document
+    pub fn push(&mut self, tags: &Tags) {
+        if self.documents.is_empty() {
+            self.document("");
+        }
+
+        let mut paragraph = String::new();
+        let mut entities = 0;
+
+        for tag in &tags.0 {
+            match tag {
+                Tag::Tagged(span) => {
+                    let mut tooltip = vec![span.class.to_string()];
+
+                    if let Some(entry) = &span.entry {
+                        if let Some(identifier) = &entry.identifier {
+                            tooltip.push(format!("id: {}", identifier));
+                        }
+
+                        tooltip.push(format!("dictionary: {}", entry.source));
+                    }
+
+                    if let Some(fuzzy) = &span.fuzzy {
+                        tooltip.push(format!("≈ {} ({})", fuzzy.term, fuzzy.distance));
+                    }
+
+                    paragraph.push_str(&format!(
+                        r#"<mark data-class="{}" title="{}">{}</mark>"#,
+                        escape(&span.class),
+                        escape(&tooltip.join("\n")).replace('\n', "&#10;"),
+                        escape(&span.original_text)
+                    ));
+
+                    *self.counts.entry(span.class.to_string()).or_default() += 1;
+                    entities += 1;
+                }
+                Tag::UnTagged(content) => paragraph.push_str(&escape(&content.original_text)),
+            }
+        }
+
+        let document = self.documents.last_mut().expect("A document is started");
+        document.paragraphs.push(paragraph);
+        document.entities += entities;
+    }
+
+    /// CSS rules coloring the spans of each class
+    fn class_styles(&self) -> String {
+        self.counts
+            .keys()
+            .zip(PALETTE.iter().cycle())
+            .map(|(class, color)| {
+                format!(
+                    "mark[data-class=\"{}\"]{{background:{}}}",
+                    css_escape(class),
+                    color
+                )
+            })
+            .collect()
+    }
+}
+
+/// Escapes a value for a double-quoted CSS string. Quotes and backslashes are backslash-escaped,
+/// control characters and HTML special characters (`</style>` would close the style element)
+/// are hexadecimal escapes
+fn css_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '<' | '>' | '&' | '\u{0}'..='\u{1f}' | '\u{7f}' => {
+                escaped.push_str(&format!("\\{:x} ", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+impl fmt::Display for HtmlReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "<!DOCTYPE html>")?;
+        writeln!(
+            f,
+            r#"<html><head><meta charset="utf-8"><title>Entities</title>"#
+        )?;
+        writeln!(f, "<style>{}{}</style>", STYLE, self.class_styles())?;
+        writeln!(f, "</head><body>")?;
+
+        writeln!(f, r#"<h1>Entities</h1><p class="legend">"#)?;
+        for (class, count) in &self.counts {
+            writeln!(
+                f,
+                r#"<span><mark data-class="{0}">{0}</mark> {1}</span>"#,
+                escape(class),
+                count
+            )?;
+        }
+        writeln!(f, "</p>")?;
+
+        writeln!(f, "<nav><ol>")?;
+        for (index, document) in self.documents.iter().enumerate() {
+            writeln!(
+                f,
+                r##"<li><a href="#document-{}">{}</a> ({} entities)</li>"##,
+                index + 1,
+                escape(&document.title),
+                document.entities
+            )?;
+        }
+        writeln!(f, "</ol></nav>")?;
+
+        for (index, document) in self.documents.iter().enumerate() {
+            writeln!(
+                f,
+                r#"<section id="document-{}"><h2>{}</h2>"#,
+                index + 1,
+                escape(&document.title)
+            )?;
+
+            for paragraph in &document.paragraphs {
+                writeln!(f, "<p>{}</p>", paragraph)?;
+            }
+
+            writeln!(f, "</section>")?;
+        }
+
+        write!(f, "</body></html>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::TaggedContent;
+
+    #[test]
+    fn spans_are_highlighted_and_counted() {
+        let text = "Lyon & Paris";
+        let spans = [
+            TaggedContent::new("Lyon", 0, 3, "LOC").with_entry("Lyon", Some("Q456"), "d.tsv", 0),
+            TaggedContent::new("Paris", 7, 11, "LOC"),
+        ];
+
+        let mut report = HtmlReport::default();
+        report.document("a.txt");
+        report.push(&Tags::from_spans(text, &spans));
+
+        assert_eq!(
+            report.documents[0].paragraphs[0],
+            r#"<mark data-class="LOC" title="LOC&#10;id: Q456&#10;dictionary: d.tsv">Lyon</mark> &amp; <mark data-class="LOC" title="LOC">Paris</mark>"#
+        );
+        assert_eq!(report.counts["LOC"], 2);
+
+        let html = report.to_string();
+        assert!(html.contains(r##"<li><a href="#document-1">a.txt</a> (2 entities)</li>"##));
+        assert!(html.contains(r##"mark[data-class="LOC"]{background:#ffd8a8}"##));
+    }
+
+    #[test]
+    fn class_selectors_are_css_escaped() {
+        let mut report = HtmlReport::default();
+        report.counts.insert("A&B \"C\"\\\n</style>".to_string(), 1);
+
+        assert_eq!(
+            report.class_styles(),
+            r#"mark[data-class="A\26 B \"C\"\\\a \3c /style\3e "]{background:#ffd8a8}"#
+        );
+    }
+}
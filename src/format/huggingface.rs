@@ -0,0 +1,162 @@
+use crate::errors::UnknownLabel;
+use crate::format::{Scheme, SchemeTags};
+use serde::Serialize;
+use std::fmt;
+
+/// Maps labels to the integer ids of token classification models: `O` is 0, followed by the labels
+/// of each class (classes sorted by name, prefixes in the order of the scheme, e.g. `B-`, `I-`, `E-`, `S-`).
+/// Every label of a class has an id, even when no token is labeled with it, so that ids only
+/// depend on the classes of the dictionary and on the scheme
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelMap {
+    labels: Vec<String>,
+}
+
+impl LabelMap {
+    pub fn new(classes: &[&str], scheme: Scheme) -> LabelMap {
+        let mut labels = vec!["O".to_string()];
+
+        for class in classes {
+            for prefix in scheme.prefixes() {
+                labels.push(format!("{}-{}", prefix, class));
+            }
+        }
+
+        LabelMap { labels }
+    }
+
+    /// Returns the id of a label
+    pub fn id(&self, label: &str) -> Result<usize, UnknownLabel> {
+        self.labels
+            .iter()
+            .position(|known| known == label)
+            .ok_or_else(|| UnknownLabel(label.to_string()))
+    }
+}
+
+/// Prints the `label2id` JSON object, labels in id order
+impl fmt::Display for LabelMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{{")?;
+
+        for (id, label) in self.labels.iter().enumerate() {
+            let separator = if id + 1 < self.labels.len() { "," } else { "" };
+            let label = serde_json::to_string(label).map_err(|_| fmt::Error)?;
+
+            writeln!(f, "  {}: {}{}", label, id, separator)?;
+        }
+
+        write!(f, "}}")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// Token classification record of Hugging Face datasets (JSONL):
+/// `{"id": "0", "tokens": ["Né", "à", "Lyon"], "ner_tags": [0, 0, 1]}`.
+/// Tags are the ids of the labels given by a `LabelMap`
+pub struct HuggingFaceRecord {
+    id: String,
+    tokens: Vec<String>,
+    ner_tags: Vec<usize>,
+}
+
+impl HuggingFaceRecord {
+    /// Builds the records of a sentence, split into records of at most `max_tokens` tokens.
+    /// Records are split between entities, unless an entity is longer than `max_tokens`.
+    /// Sentences without tokens have no record
+    pub fn from_tags(
+        tags: &SchemeTags,
+        labels: &LabelMap,
+        max_tokens: usize,
+    ) -> Result<Vec<HuggingFaceRecord>, UnknownLabel> {
+        let max_tokens = max_tokens.max(1);
+        let entities = tags.entities();
+        let length = tags.tags().len();
+        let mut records = vec![];
+        let mut start = 0;
+
+        while start < length {
+            let mut end = length;
+
+            if end - start > max_tokens {
+                end = (start + 1..=start + max_tokens)
+                    .rev()
+                    .find(|end| {
+                        !entities
+                            .iter()
+                            .any(|(first, last)| first < end && end <= last)
+                    })
+                    .unwrap_or(start + max_tokens);
+            }
+
+            let tokens = &tags.tags()[start..end];
+
+            records.push(HuggingFaceRecord {
+                id: String::new(),
+                tokens: tokens.iter().map(|tag| tag.text().to_string()).collect(),
+                ner_tags: tokens
+                    .iter()
+                    .map(|tag| labels.id(&tag.label()))
+                    .collect::<Result<_, _>>()?,
+            });
+
+            start = end;
+        }
+
+        Ok(records)
+    }
+
+    /// Sets the id of the record
+    pub fn with_id(mut self, id: usize) -> HuggingFaceRecord {
+        self.id = id.to_string();
+        self
+    }
+}
+
+/// Prints the record on a single line
+impl fmt::Display for HuggingFaceRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::WhitespaceTokenizer;
+    use crate::tagger::{TaggedContent, Tags};
+
+    #[test]
+    fn sentences_are_split_between_entities() {
+        let text = "a Centre Léon Bérard b c";
+        let start = text.find("Centre").unwrap();
+        let end = text.find(" b").unwrap() - 1;
+        let spans = [TaggedContent::new(&text[start..=end], start, end, "ORG")];
+        let tags = SchemeTags::from_tags(
+            Tags::from_spans(text, &spans),
+            Scheme::BIOES,
+            &WhitespaceTokenizer,
+        );
+
+        let labels = LabelMap::new(&["LOC", "ORG"], Scheme::BIOES);
+        assert_eq!(labels.id("S-LOC").unwrap(), 4);
+        assert_eq!(labels.id("B-ORG").unwrap(), 5);
+
+        let records = HuggingFaceRecord::from_tags(&tags, &labels, 3).unwrap();
+        let records = records
+            .into_iter()
+            .enumerate()
+            .map(|(id, record)| record.with_id(id).to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            records,
+            [
+                r#"{"id":"0","tokens":["a"],"ner_tags":[0]}"#,
+                r#"{"id":"1","tokens":["Centre","Léon","Bérard"],"ner_tags":[5,6,7]}"#,
+                r#"{"id":"2","tokens":["b","c"],"ner_tags":[0,0]}"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use crate::format::AnnotatedText;
+use crate::tagger::{Tag, TaggedContent, Tags};
+use quick_xml::escape::{escape, unescape};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Opening and closing markup of a span. `{class}` is replaced by the class of the span
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineTemplate {
+    pub open: String,
+    pub close: String,
+}
+
+impl InlineTemplate {
+    pub fn new<S: Into<String>>(open: S, close: S) -> InlineTemplate {
+        InlineTemplate {
+            open: open.into(),
+            close: close.into(),
+        }
+    }
+}
+
+/// Renders tags as text with inline markup (e.g. `<LOC>Paris</LOC>`, `[Paris](LOC)`)
+/// and parses such markup back into tags.
+/// Templates set for a class take precedence over the default template.
+/// XML markup escapes the text, so that `<`, `>` and `&` of the text are never read as markup
+#[derive(Debug, Clone)]
+pub struct InlineMarkup {
+    default: InlineTemplate,
+    classes: HashMap<String, InlineTemplate>,
+    escape: bool,
+}
+
+/// Part of a template matched by the parser: literal text around an optional `{class}`
+#[derive(Debug, Clone)]
+struct Marker<'t> {
+    prefix: &'t str,
+    suffix: Option<&'t str>,
+}
+
+impl<'t> Marker<'t> {
+    fn new(template: &'t str) -> Marker<'t> {
+        match template.split_once("{class}") {
+            Some((prefix, suffix)) => Marker {
+                prefix,
+                suffix: Some(suffix),
+            },
+            None => Marker {
+                prefix: template,
+                suffix: None,
+            },
+        }
+    }
+
+    /// Matches the marker at the start of the input. Returns the length of the marker and
+    /// the class it holds. Classes are single words
+    fn find(&self, input: &'t str) -> Option<(usize, Option<&'t str>)> {
+        let rest = input.strip_prefix(self.prefix)?;
+
+        match self.suffix {
+            None => Some((self.prefix.len(), None)),
+            Some(suffix) => {
+                let end = match suffix {
+                    "" => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+                    _ => rest.find(suffix)?,
+                };
+                let class = &rest[..end];
+
+                if class.is_empty() || class.contains(char::is_whitespace) {
+                    return None;
+                }
+
+                Some((self.prefix.len() + end + suffix.len(), Some(class)))
+            }
+        }
+    }
+}
+
+impl InlineMarkup {
+    /// XML markup: `<CLASS>text</CLASS>`, the text is escaped
+    pub fn xml() -> InlineMarkup {
+        InlineMarkup {
+            default: InlineTemplate::new("<{class}>", "</{class}>"),
+            classes: HashMap::new(),
+            escape: true,
+        }
+    }
+
+    /// Markdown link markup: `[text](CLASS)`. The text isn't escaped
+    pub fn markdown() -> InlineMarkup {
+        InlineMarkup {
+            default: InlineTemplate::new("[", "]({class})"),
+            classes: HashMap::new(),
+            escape: false,
+        }
+    }
+
+    /// Sets the template of a class
+    pub fn class<S: Into<String>>(mut self, class: S, template: InlineTemplate) -> InlineMarkup {
+        self.classes.insert(class.into(), template);
+        self
+    }
+
+    fn template(&self, class: &str) -> &InlineTemplate {
+        self.classes.get(class).unwrap_or(&self.default)
+    }
+
+    fn escape<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        match self.escape {
+            true => escape(text),
+            false => Cow::Borrowed(text),
+        }
+    }
+
+    fn unescape<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        match self.escape {
+            true => unescape(text).unwrap_or(Cow::Borrowed(text)),
+            false => Cow::Borrowed(text),
+        }
+    }
+
+    /// Renders tags as text with inline markup
+    pub fn render(&self, tags: &Tags) -> String {
+        let mut rendered = String::new();
+
+        for tag in &tags.0 {
+            match tag {
+                Tag::Tagged(span) => {
+                    let template = self.template(&span.class);
+
+                    rendered.push_str(&template.open.replace("{class}", &span.class));
+                    rendered.push_str(&self.escape(&span.original_text));
+                    rendered.push_str(&template.close.replace("{class}", &span.class));
+                }
+                Tag::UnTagged(content) => rendered.push_str(&self.escape(&content.original_text)),
+            }
+        }
+
+        rendered
+    }
+
+    /// Returns the opening and closing markup of each class template, then of the default template
+    fn templates(&self) -> Vec<(Option<&str>, String, String)> {
+        self.classes
+            .iter()
+            .map(|(class, template)| {
+                (
+                    Some(class.as_str()),
+                    template.open.replace("{class}", class),
+                    template.close.replace("{class}", class),
+                )
+            })
+            .chain(std::iter::once((
+                None,
+                self.default.open.clone(),
+                self.default.close.clone(),
+            )))
+            .collect()
+    }
+
+    /// Finds a span at the start of the input. Returns the length of the span markup,
+    /// the text of the span and its class
+    fn span<'t>(
+        templates: &[(Option<&str>, String, String)],
+        input: &'t str,
+    ) -> Option<(usize, &'t str, String)> {
+        for (fixed_class, open, close) in templates {
+            let (open_length, open_class) = match Marker::new(open).find(input) {
+                Some(found) => found,
+                None => continue,
+            };
+
+            let class = fixed_class.or(open_class).unwrap_or("");
+            let close = Marker::new(close);
+            let content = &input[open_length..];
+
+            for (end, _) in content.char_indices().skip(1) {
+                match close.find(&content[end..]) {
+                    Some((close_length, close_class))
+                        if close_class.is_none_or(|c| class.is_empty() || c == class) =>
+                    {
+                        let class = close_class.unwrap_or(class);
+
+                        if class.is_empty() {
+                            break;
+                        }
+
+                        return Some((
+                            open_length + end + close_length,
+                            &content[..end],
+                            class.to_string(),
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Parses text with inline markup into the text and its tags. Byte offsets of the tags
+    /// refer to the text without markup
+    pub fn parse(&self, input: &str) -> AnnotatedText {
+        let templates = self.templates();
+        let mut text = String::new();
+        let mut spans = vec![];
+        let mut plain_start = 0;
+        let mut position = 0;
+
+        while position < input.len() {
+            match InlineMarkup::span(&templates, &input[position..]) {
+                Some((length, content, class)) => {
+                    text.push_str(&self.unescape(&input[plain_start..position]));
+
+                    let content = self.unescape(content);
+                    let start = text.len();
+                    text.push_str(&content);
+
+                    spans.push(TaggedContent::new(
+                        content.into_owned(),
+                        start,
+                        text.len() - 1,
+                        class,
+                    ));
+
+                    position += length;
+                    plain_start = position;
+                }
+                None => {
+                    position += input[position..].chars().next().map_or(1, char::len_utf8);
+                }
+            }
+        }
+
+        text.push_str(&self.unescape(&input[plain_start..]));
+
+        AnnotatedText::new(text, spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Né à Lyon & <près> [de] Curie";
+
+    fn tags() -> Tags<'static> {
+        Tags::from_spans(TEXT, &expected())
+    }
+
+    fn expected() -> Vec<TaggedContent<'static>> {
+        ["Lyon", "Curie"]
+            .iter()
+            .zip(["LOC", "PER"].iter())
+            .map(|(term, class)| {
+                let start = TEXT.find(term).unwrap();
+                TaggedContent::new(*term, start, start + term.len() - 1, *class)
+            })
+            .collect()
+    }
+
+    fn spans(text: &AnnotatedText) -> Vec<(usize, usize, String)> {
+        text.tags()
+            .0
+            .into_iter()
+            .filter_map(|tag| match tag {
+                Tag::Tagged(span) => Some((span.start, span.end, span.class.into_owned())),
+                Tag::UnTagged(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rendered_markup_is_parsed_back() {
+        let markups = [
+            (
+                InlineMarkup::xml(),
+                "Né à <LOC>Lyon</LOC> &amp; &lt;près&gt; [de] <PER>Curie</PER>",
+            ),
+            (
+                InlineMarkup::markdown().class("PER", InlineTemplate::new("{{", "}}")),
+                "Né à [Lyon](LOC) & <près> [de] {{Curie}}",
+            ),
+        ];
+
+        for (markup, rendered) in markups.iter() {
+            assert_eq!(markup.render(&tags()), *rendered);
+
+            let parsed = markup.parse(rendered);
+            assert_eq!(parsed.text, TEXT);
+            assert_eq!(
+                spans(&parsed),
+                spans(&AnnotatedText::new(TEXT.to_string(), expected()))
+            );
+        }
+    }
+}
@@ -1,24 +1,55 @@
+use crate::format::WhitespaceTokenizer;
 use crate::tagger::Tags;
 use crate::tagger::{Tag, TaggedContent, UntaggedContent};
-use crate::traits::PrettyDisplay;
+use crate::traits::{PrettyDisplay, Tokenizer};
+use crate::types::*;
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// Inside Outside Beginning tagging format
-pub struct IOB {
-    tags: Vec<IOBTag>,
+pub struct IOB<'a> {
+    tags: Vec<IOBTag<'a>>,
 }
 
 /// Tags of the IOB format
-#[derive(Debug, Clone)]
-pub enum IOBTag {
-    Inside(TaggedContent),
-    Outside(UntaggedContent),
-    Beginning(TaggedContent),
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IOBTag<'a> {
+    Inside(TaggedContent<'a>),
+    Outside(UntaggedContent<'a>),
+    Beginning(TaggedContent<'a>),
 }
 
-impl<'a> PrettyDisplay for IOBTag {
+impl IOBTag<'_> {
+    /// Returns the label of a tag (e.g. `B-LOC`, `O`)
+    pub fn label(&self) -> String {
+        match self {
+            Self::Inside(tag) => format!("I-{}", tag.class),
+            Self::Outside(_) => "O".to_string(),
+            Self::Beginning(tag) => format!("B-{}", tag.class),
+        }
+    }
+
+    /// Returns the text of a tag
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Inside(tag) | Self::Beginning(tag) => &tag.original_text,
+            Self::Outside(tag) => &tag.original_text,
+        }
+    }
+
+    /// Returns the position of a tag in the text: first byte and last byte (inclusive end)
+    #[allow(dead_code)]
+    pub fn span(&self) -> (StartByte, EndByte) {
+        match self {
+            Self::Inside(tag) | Self::Beginning(tag) => (tag.start, tag.end),
+            Self::Outside(tag) => (tag.start, tag.end),
+        }
+    }
+}
+
+impl PrettyDisplay for IOBTag<'_> {
     fn pretty_display(&self) -> String {
         match self {
             Self::Beginning(tag) => format!(
@@ -77,36 +108,56 @@ macro_rules! iob_b {
     };
 
     ($tagged_content:expr) => {
-        IOBTag::Outside($tagged_content)
+        IOBTag::Beginning($tagged_content)
     };
 }
 
-impl<'a> From<Tags> for IOB {
-    fn from(tags: Tags) -> Self {
-        let mut tgs: Vec<IOBTag> = vec![];
+impl<'a> IOB<'a> {
+    /// Encodes tags in IOB. Each tag is split into tokens by the given tokenizer
+    pub fn from_tags(tags: Tags<'a>, tokenizer: &dyn Tokenizer) -> IOB<'a> {
+        let mut iob_tags: Vec<IOBTag> = vec![];
 
         for tag in tags.0 {
             match tag {
-                Tag::Tagged(t) => tgs.append(&mut t.into()),
-                Tag::UnTagged(t) => tgs.append(&mut t.into()),
+                Tag::Tagged(t) => {
+                    for (position, token) in t.tokens(tokenizer).into_iter().enumerate() {
+                        match position {
+                            0 => iob_tags.push(iob_b!(token)),
+                            _ => iob_tags.push(iob_i!(token)),
+                        }
+                    }
+                }
+                Tag::UnTagged(t) => {
+                    iob_tags.extend(t.tokens(tokenizer).into_iter().map(|token| iob_o!(token)))
+                }
             }
         }
 
-        IOB { tags: tgs }
+        IOB { tags: iob_tags }
     }
 }
 
-impl<'a> fmt::Display for IOBTag {
+/// Encodes tags in IOB, tokens are separated by whitespaces
+impl<'a> From<Tags<'a>> for IOB<'a> {
+    fn from(tags: Tags<'a>) -> Self {
+        IOB::from_tags(tags, &WhitespaceTokenizer)
+    }
+}
+
+impl fmt::Display for IOBTag<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Inside(tag) => write!(f, "{} I-{}", tag.original_text.trim(), tag.class),
-            Self::Outside(tag) => write!(f, "{} O", tag.original_text.trim()),
-            Self::Beginning(tag) => write!(f, "{} B-{}", tag.original_text.trim(), tag.class),
-        }
+        write!(f, "{} {}", self.text().trim(), self.label())
+    }
+}
+
+impl<'a> IOB<'a> {
+    /// Returns the tags of the IOB sequence
+    pub fn tags(&self) -> &[IOBTag<'a>] {
+        &self.tags
     }
 }
 
-impl<'a> fmt::Display for IOB {
+impl fmt::Display for IOB<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let results = self
             .tags
@@ -117,78 +168,59 @@ impl<'a> fmt::Display for IOB {
     }
 }
 
-impl<'a> PrettyDisplay for IOB {
+impl PrettyDisplay for IOB<'_> {
     fn pretty_display(&self) -> String {
         let results = self
             .tags
             .iter()
-            .map(|t| format!("{}", t.pretty_display()))
+            .map(|t| t.pretty_display().to_string())
             .collect::<Vec<_>>();
-        format!("{}", results.join("\n"))
+        results.join("\n").to_string()
     }
 }
 
-impl From<TaggedContent> for Vec<IOBTag> {
-    fn from(tag: TaggedContent) -> Self {
-        let mut sub_tags = tag
-            .original_text
-            .split_terminator(|c: char| c.is_ascii_whitespace())
-            .collect::<Vec<&str>>()
-            .into_iter();
-
-        let mut iob_tags = vec![];
-        let mut last_pos = tag.start;
-
-        // Beginning tag
-        while let Some(sub_tag) = sub_tags.next() {
-            if !sub_tag.is_empty() {
-                iob_tags.push(iob_b![sub_tag, [last_pos => sub_tag.len()], &tag.class]);
-
-                last_pos += sub_tag.len();
-                break;
-            }
-
-            last_pos += 1;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::fixtures::{tags, tokenizers, TEXT};
 
-        // Inside Tag
-        while let Some(sub_tag) = sub_tags.next() {
-            if !sub_tag.is_empty() {
-                iob_tags.push(iob_i![sub_tag, [last_pos => sub_tag.len()], &tag.class]);
+    #[test]
+    fn token_offsets_slice_the_input() {
+        for tokenizer in tokenizers().iter() {
+            let iob = IOB::from_tags(tags(), *tokenizer);
 
-                last_pos += sub_tag.len();
-            } else {
-                last_pos += 1;
+            for tag in iob.tags() {
+                let (start, end) = tag.span();
+                assert_eq!(&TEXT[start..=end], tag.text());
             }
         }
-
-        iob_tags
     }
-}
 
-impl From<UntaggedContent> for Vec<IOBTag> {
-    fn from(tag: UntaggedContent) -> Self {
-        let mut sub_tags = tag
-            .original_text
-            .split_terminator(|c: char| c.is_ascii_whitespace())
-            .collect::<Vec<&str>>()
-            .into_iter()
-            .peekable();
-
-        let mut bioes_tags = vec![];
-        let mut last_pos = tag.start;
-
-        // Inside/End Tag
-        while let Some(sub_tag) = sub_tags.next() {
-            if !sub_tag.is_empty() {
-                bioes_tags.push(iob_o![sub_tag, [last_pos => sub_tag.len()]]);
-
-                last_pos += sub_tag.len();
-            } else {
-                last_pos += 1;
-            }
-        }
+    #[test]
+    fn whitespaces_are_not_tokens() {
+        let iob = IOB::from(tags());
+        let labels = iob
+            .tags()
+            .iter()
+            .map(|tag| format!("{} {}", tag.text(), tag.label()))
+            .collect::<Vec<String>>();
 
-        bioes_tags
+        assert_eq!(
+            labels,
+            [
+                "Né O",
+                "à O",
+                "Pierre-Bénite B-LOC",
+                ", O",
+                "près O",
+                "de O",
+                "l' O",
+                "Université B-LOC",
+                "de I-LOC",
+                "Lyon I-LOC",
+                "à O",
+                "7h. O"
+            ]
+        );
     }
 }
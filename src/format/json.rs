@@ -0,0 +1,224 @@
+use crate::format::{Scheme, SchemeTag, SchemeTags};
+use crate::tagger::{FuzzyMatch, OffsetConverter, Offsets, Tag, TaggedContent, Tags};
+use crate::traits::Tokenizer;
+use crate::types::*;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt;
+
+#[derive(Debug, Clone, Serialize)]
+/// JSON record of a tagged text: a line (jsonl) or a whole document (json).
+///
+/// ```text
+/// {
+///   "document": string,            // path of the document, json records only
+///   "text": string,                // the tagged text
+///   "spans": [{
+///     "start": int,                // offsets in the chosen unit and end convention
+///     "end": int,
+///     "label": string,             // class of the dictionary entry
+///     "text": string,              // matched text
+///     "term": string | null,       // dictionary term
+///     "identifier": string | null, // identifier of the dictionary entry
+///     "source": string | null,     // dictionary file
+///     "priority": int | null,      // priority of the dictionary file
+///     "fuzzy": {"term": string, "distance": int} | null
+///   }],
+///   "dropped": [...],              // spans dropped because of their capitalization
+///   "tokens": [{"start": int, "end": int, "text": string, "label": string}]
+/// }
+/// ```
+///
+/// Token labels follow the chosen labeling scheme. Fields are never removed nor renamed
+pub struct JsonRecord<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    document: Option<Cow<'a, str>>,
+    text: Cow<'a, str>,
+    spans: Vec<JsonSpan<'a>>,
+    dropped: Vec<JsonSpan<'a>>,
+    tokens: Vec<JsonToken<'a>>,
+    #[serde(skip)]
+    offsets: Offsets,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// A tagged span of a JSON record
+pub struct JsonSpan<'a> {
+    start: usize,
+    end: usize,
+    label: Cow<'a, str>,
+    text: Cow<'a, str>,
+    term: Option<Cow<'a, str>>,
+    identifier: Option<Cow<'a, str>>,
+    source: Option<Cow<'a, str>>,
+    priority: Option<Priority>,
+    fuzzy: Option<FuzzyMatch<'a>>,
+}
+
+impl<'a> JsonSpan<'a> {
+    fn new(span: &TaggedContent<'a>, converter: &OffsetConverter) -> JsonSpan<'a> {
+        let (start, end) = converter.span(span.start, span.end);
+
+        JsonSpan {
+            start,
+            end,
+            label: span.class.clone(),
+            text: span.original_text.clone(),
+            term: span.entry.as_ref().map(|entry| entry.term.clone()),
+            identifier: span
+                .entry
+                .as_ref()
+                .and_then(|entry| entry.identifier.clone()),
+            source: span.entry.as_ref().map(|entry| entry.source.clone()),
+            priority: span.entry.as_ref().map(|entry| entry.priority),
+            fuzzy: span.fuzzy.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// A labeled token of a JSON record
+pub struct JsonToken<'a> {
+    start: usize,
+    end: usize,
+    text: Cow<'a, str>,
+    label: String,
+}
+
+impl<'a> JsonRecord<'a> {
+    /// Builds the record of a tagged text. Tags are split into tokens by the given tokenizer
+    pub fn new(
+        text: &'a str,
+        tags: Tags<'a>,
+        scheme: Scheme,
+        tokenizer: &dyn Tokenizer,
+        offsets: Offsets,
+    ) -> JsonRecord<'a> {
+        let converter = OffsetConverter::new(text, offsets);
+
+        let spans = tags
+            .0
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::Tagged(span) => Some(span),
+                Tag::UnTagged(_) => None,
+            })
+            .map(|span| JsonSpan::new(span, &converter))
+            .collect();
+
+        let tokens = SchemeTags::from_tags(tags, scheme, tokenizer)
+            .tags()
+            .iter()
+            .map(|tag| {
+                let (start, end) = tag.span();
+                let (start, end) = converter.span(start, end);
+
+                let text = match tag {
+                    SchemeTag::Entity(_, token) => token.original_text.clone(),
+                    SchemeTag::Outside(token) => token.original_text.clone(),
+                };
+
+                JsonToken {
+                    start,
+                    end,
+                    text,
+                    label: tag.label(),
+                }
+            })
+            .collect();
+
+        JsonRecord {
+            document: None,
+            text: Cow::Borrowed(text),
+            spans,
+            dropped: vec![],
+            tokens,
+            offsets,
+        }
+    }
+
+    /// Sets the spans dropped because of their capitalization
+    pub fn with_dropped(mut self, dropped: &[TaggedContent<'a>]) -> JsonRecord<'a> {
+        let converter = OffsetConverter::new(&self.text, self.offsets);
+
+        self.dropped = dropped
+            .iter()
+            .map(|span| JsonSpan::new(span, &converter))
+            .collect();
+        self
+    }
+
+    /// Sets the path of the document the record stands for
+    pub fn with_document<S: Into<Cow<'a, str>>>(mut self, document: S) -> JsonRecord<'a> {
+        self.document = Some(document.into());
+        self
+    }
+}
+
+/// Prints the record on a single line (JSON lines)
+impl fmt::Display for JsonRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::WhitespaceTokenizer;
+    use crate::tagger::{EndConvention, OffsetUnit};
+
+    #[test]
+    fn records_follow_the_schema() {
+        let text = "À Lyon";
+        let spans = [TaggedContent::new("Lyon", 3, 6, "LOC").with_entry(
+            "lyon",
+            Some("Q456"),
+            "dict.tsv",
+            2,
+        )];
+        let offsets = Offsets {
+            unit: OffsetUnit::Chars,
+            end: EndConvention::Exclusive,
+        };
+
+        let record = JsonRecord::new(
+            text,
+            Tags::from_spans(text, &spans),
+            Scheme::BIOES,
+            &WhitespaceTokenizer,
+            offsets,
+        );
+
+        assert_eq!(
+            record.to_string(),
+            concat!(
+                r#"{"text":"À Lyon","spans":[{"start":2,"end":6,"label":"LOC","text":"Lyon","#,
+                r#""term":"lyon","identifier":"Q456","source":"dict.tsv","priority":2,"fuzzy":null}],"#,
+                r#""dropped":[],"#,
+                r#""tokens":[{"start":0,"end":1,"text":"À","label":"O"},"#,
+                r#"{"start":2,"end":6,"text":"Lyon","label":"S-LOC"}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn dropped_spans_are_reported() {
+        let text = "à lyon";
+        let dropped = [TaggedContent::new("lyon", 3, 6, "LOC")];
+
+        let record = JsonRecord::new(
+            text,
+            Tags::from_spans(text, &[]),
+            Scheme::IOB2,
+            &WhitespaceTokenizer,
+            Offsets::default(),
+        )
+        .with_dropped(&dropped)
+        .to_string();
+
+        assert!(record
+            .contains(r#""spans":[],"dropped":[{"start":3,"end":6,"label":"LOC","text":"lyon""#));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::format::{char_spans, AnnotatedText};
+use crate::tagger::Tags;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+/// Version reported in the predictions of Label Studio tasks
+const MODEL_VERSION: &str = concat!(env!("CARGO_PKG_NAME"), "-", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Label Studio tasks (a single JSON array for every tagged document) with spans as `predictions`. Results target the labeling
+/// config `<Labels name="label" toName="text">` / `<Text name="text" value="$text">`.
+/// Offsets are character offsets with an exclusive end.
+/// The first annotation which wasn't cancelled is read from exports, predictions otherwise
+pub struct LabelStudio<'a>(pub Vec<LabelStudioTask<'a>>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelStudioTask<'a> {
+    data: LabelStudioData<'a>,
+    #[serde(default)]
+    predictions: Vec<LabelStudioResults<'a>>,
+    #[serde(default, skip_serializing)]
+    annotations: Vec<LabelStudioResults<'a>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelStudioData<'a> {
+    text: Cow<'a, str>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Results of a prediction or an annotation
+pub struct LabelStudioResults<'a> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model_version: Option<Cow<'a, str>>,
+    #[serde(default)]
+    result: Vec<LabelStudioResult<'a>>,
+    #[serde(default, skip_serializing)]
+    was_cancelled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelStudioResult<'a> {
+    #[serde(default)]
+    id: Option<String>,
+    from_name: Cow<'a, str>,
+    to_name: Cow<'a, str>,
+    #[serde(rename = "type")]
+    kind: Cow<'a, str>,
+    value: LabelStudioValue<'a>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Value of a result. Only results of type `labels` have offsets
+pub struct LabelStudioValue<'a> {
+    #[serde(default)]
+    start: Option<usize>,
+    #[serde(default)]
+    end: Option<usize>,
+    #[serde(default)]
+    text: Option<Cow<'a, str>>,
+    #[serde(default)]
+    labels: Vec<Cow<'a, str>>,
+}
+
+impl LabelStudioTask<'_> {
+    /// Builds the Label Studio task of a tagged text. The task owns its strings, so that the tasks
+    /// of every document can be printed together
+    pub fn from_tags(text: &str, tags: &Tags) -> LabelStudioTask<'static> {
+        let result = char_spans(text, tags)
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, end, span))| LabelStudioResult {
+                id: Some(format!("T{}", index + 1)),
+                from_name: Cow::Borrowed("label"),
+                to_name: Cow::Borrowed("text"),
+                kind: Cow::Borrowed("labels"),
+                value: LabelStudioValue {
+                    start: Some(start),
+                    end: Some(end),
+                    text: Some(Cow::Owned(span.original_text.to_string())),
+                    labels: vec![Cow::Owned(span.class.to_string())],
+                },
+            })
+            .collect();
+
+        LabelStudioTask {
+            data: LabelStudioData {
+                text: Cow::Owned(text.to_string()),
+            },
+            predictions: vec![LabelStudioResults {
+                model_version: Some(Cow::Borrowed(MODEL_VERSION)),
+                result,
+                was_cancelled: false,
+            }],
+            annotations: vec![],
+        }
+    }
+}
+
+impl LabelStudio<'_> {
+    /// Reads the tasks of a Label Studio JSON export. Consecutive arrays of tasks are read
+    /// as one array
+    pub fn read(input: &str) -> Result<Vec<AnnotatedText>, Box<dyn Error>> {
+        let mut tasks = vec![];
+        let mut texts = vec![];
+
+        for array in serde_json::Deserializer::from_str(input).into_iter::<LabelStudio>() {
+            tasks.extend(array?.0);
+        }
+
+        for task in tasks {
+            let LabelStudioTask {
+                data,
+                predictions,
+                annotations,
+            } = task;
+
+            let results = annotations
+                .into_iter()
+                .find(|annotation| !annotation.was_cancelled)
+                .or_else(|| predictions.into_iter().next())
+                .map(|results| results.result)
+                .unwrap_or_default();
+
+            let mut spans = vec![];
+
+            for result in results.into_iter().filter(|result| result.kind == "labels") {
+                if let (Some(start), Some(end)) = (result.value.start, result.value.end) {
+                    spans.extend(
+                        result
+                            .value
+                            .labels
+                            .into_iter()
+                            .map(|label| (start, end, label.into_owned())),
+                    );
+                }
+            }
+
+            texts.push(AnnotatedText::from_char_spans(
+                data.text.into_owned(),
+                spans,
+            )?);
+        }
+
+        Ok(texts)
+    }
+}
+
+impl fmt::Display for LabelStudio<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string_pretty(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::Tag;
+
+    #[test]
+    fn annotations_are_read_before_predictions() {
+        let result = |label: &str| {
+            format!(
+                r#"{{"from_name":"label","to_name":"text","type":"labels","value":{{"start":0,"end":4,"labels":["{}"]}}}}"#,
+                label
+            )
+        };
+        let export = format!(
+            r#"[{{"data":{{"text":"Lyon"}},"annotations":[{{"was_cancelled":true,"result":[{}]}},{{"result":[{}]}}],"predictions":[{{"result":[{}]}}]}}]"#,
+            result("PER"),
+            result("LOC"),
+            result("ORG")
+        );
+
+        let texts = LabelStudio::read(&export).unwrap();
+
+        match &texts[0].tags().0[..] {
+            [Tag::Tagged(span)] => assert_eq!(span.class, "LOC"),
+            _ => panic!("Lyon isn't tagged"),
+        }
+    }
+}
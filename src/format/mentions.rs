@@ -0,0 +1,211 @@
+use crate::tagger::{OffsetConverter, Offsets, Tag, Tags};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Columns of a mention table, context columns excluded
+const COLUMNS: [&str; 8] = [
+    "document",
+    "line",
+    "start",
+    "end",
+    "class",
+    "text",
+    "term",
+    "identifier",
+];
+
+/// Field delimiter of a mention table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    /// Tab-separated fields. Backslashes, tabs and line breaks of fields are escaped (`\\`, `\t`, `\n`, `\r`)
+    TSV,
+    /// Comma-separated fields, quoted when needed (RFC 4180)
+    CSV,
+}
+
+/// Options of the mention table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MentionsOptions {
+    pub format: TableFormat,
+    /// Number of characters of context on each side of a mention. No context columns when 0
+    pub context: usize,
+    /// Unit and end convention of the offsets, relative to the line
+    pub offsets: Offsets,
+}
+
+impl Default for MentionsOptions {
+    fn default() -> Self {
+        MentionsOptions {
+            format: TableFormat::TSV,
+            context: 0,
+            offsets: Offsets::default(),
+        }
+    }
+}
+
+impl MentionsOptions {
+    /// Formats a field
+    fn field<'t>(&self, field: &'t str) -> Cow<'t, str> {
+        match self.format {
+            TableFormat::TSV if field.contains(['\\', '\t', '\n', '\r']) => Cow::Owned(
+                field
+                    .replace('\\', "\\\\")
+                    .replace('\t', "\\t")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r"),
+            ),
+            TableFormat::CSV if field.contains([',', '"', '\n', '\r']) => {
+                Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+            }
+            _ => Cow::Borrowed(field),
+        }
+    }
+
+    /// Joins the fields of a row
+    fn row<S: AsRef<str>>(&self, fields: &[S]) -> String {
+        let delimiter = match self.format {
+            TableFormat::TSV => "\t",
+            TableFormat::CSV => ",",
+        };
+
+        fields
+            .iter()
+            .map(|field| self.field(field.as_ref()))
+            .collect::<Vec<Cow<str>>>()
+            .join(delimiter)
+    }
+
+    /// Header row of the table
+    pub fn header(&self) -> String {
+        let mut columns = COLUMNS.to_vec();
+
+        if self.context > 0 {
+            columns.extend(["left_context", "right_context"]);
+        }
+
+        self.row(&columns)
+    }
+}
+
+/// Flat table of the mentions of a document (TSV or CSV): one row per tagged span with its document,
+/// its line number (from 1), its offsets within the line, its class, the matched text,
+/// the dictionary term and the identifier of the entry.
+/// Rows optionally end with the untagged text around the mention
+#[derive(Debug, Clone)]
+pub struct Mentions<'a> {
+    document: Cow<'a, str>,
+    rows: Vec<String>,
+    options: MentionsOptions,
+}
+
+impl<'a> Mentions<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(document: S, options: MentionsOptions) -> Mentions<'a> {
+        Mentions {
+            document: document.into(),
+            rows: vec![],
+            options,
+        }
+    }
+
+    /// Adds the mentions of a tagged line
+    pub fn push(&mut self, line_number: usize, line: &str, tags: &Tags) {
+        let converter = OffsetConverter::new(line, self.options.offsets);
+
+        for tag in &tags.0 {
+            let span = match tag {
+                Tag::Tagged(span) => span,
+                Tag::UnTagged(_) => continue,
+            };
+
+            let (start, end) = converter.span(span.start, span.end);
+            let entry = span.entry.as_ref();
+
+            let mut fields = vec![
+                self.document.to_string(),
+                line_number.to_string(),
+                start.to_string(),
+                end.to_string(),
+                span.class.to_string(),
+                span.original_text.to_string(),
+                entry.map_or(String::new(), |entry| entry.term.to_string()),
+                entry
+                    .and_then(|entry| entry.identifier.as_ref())
+                    .map_or(String::new(), |identifier| identifier.to_string()),
+            ];
+
+            if self.options.context > 0 {
+                let before = &line[..span.start];
+                let after = &line[span.end + 1..];
+                let left_start = before
+                    .char_indices()
+                    .rev()
+                    .nth(self.options.context - 1)
+                    .map_or(0, |(index, _)| index);
+                let right_end = after
+                    .char_indices()
+                    .nth(self.options.context)
+                    .map_or(after.len(), |(index, _)| index);
+
+                fields.push(before[left_start..].to_string());
+                fields.push(after[..right_end].to_string());
+            }
+
+            self.rows.push(self.options.row(&fields));
+        }
+    }
+}
+
+/// Prints the rows, without header
+impl fmt::Display for Mentions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.rows {
+            writeln!(f, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::{EndConvention, OffsetUnit, TaggedContent};
+
+    #[test]
+    fn fields_are_quoted_and_context_is_cut() {
+        let line = "Né à Lyon, \"vers\" Curie";
+        let start = line.find("Lyon").unwrap();
+        let spans = [
+            TaggedContent::new("Lyon", start, start + 3, "LOC").with_entry(
+                "lyon",
+                Some("Q456"),
+                "d.tsv",
+                0,
+            ),
+        ];
+        let tags = Tags::from_spans(line, &spans);
+
+        let options = MentionsOptions {
+            format: TableFormat::CSV,
+            context: 3,
+            offsets: Offsets {
+                unit: OffsetUnit::Chars,
+                end: EndConvention::Exclusive,
+            },
+        };
+        let mut mentions = Mentions::new("a,b.txt", options.clone());
+        mentions.push(2, line, &tags);
+
+        assert_eq!(
+            options.header(),
+            "document,line,start,end,class,text,term,identifier,left_context,right_context"
+        );
+        assert_eq!(
+            mentions.to_string(),
+            "\"a,b.txt\",2,5,9,LOC,Lyon,lyon,Q456, à ,\", \"\"\"\n"
+        );
+
+        let options = MentionsOptions::default();
+        assert_eq!(options.row(&["a\tb", "c\\d"]), "a\\tb\tc\\\\d");
+    }
+}
@@ -1,5 +1,39 @@
+mod annotated;
 mod bioes;
+mod brat;
+mod conll;
+mod doccano;
+#[cfg(test)]
+mod fixtures;
+mod html;
+mod huggingface;
+mod inline;
 mod iob;
+mod json;
+mod label_studio;
+mod mentions;
+mod nested;
+mod prodigy;
+mod scheme;
+mod standoff;
+mod tei;
+mod tokenizer;
 
+pub use annotated::*;
 pub use bioes::*;
+pub use brat::*;
+pub use conll::*;
+pub use doccano::*;
+pub use html::*;
+pub use huggingface::*;
+pub use inline::*;
 pub use iob::*;
+pub use json::*;
+pub use label_studio::*;
+pub use mentions::*;
+pub use nested::*;
+pub use prodigy::*;
+pub use scheme::*;
+pub use standoff::*;
+pub use tei::*;
+pub use tokenizer::*;
@@ -0,0 +1,221 @@
+use crate::format::{BIOESTag, WhitespaceTokenizer, BIOES};
+use crate::tagger::{sub_str, NestedTags, TaggedContent, UntaggedContent};
+use crate::traits::{PrettyDisplay, Tokenizer};
+use crate::types::*;
+use colored::Colorize;
+use std::borrow::Cow;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Nested BIOES tagging format. Each layer of nested tags is encoded as a BIOES column,
+/// all layers share the same tokens
+pub struct NestedBIOES<'a> {
+    layers: Vec<BIOES<'a>>,
+}
+
+impl<'a> NestedBIOES<'a> {
+    /// Splits a text into tokens, then splits tokens on every span boundary, so that tokens line up across layers
+    fn tokenize(
+        text: &str,
+        cuts: &[usize],
+        tokenizer: &dyn Tokenizer,
+    ) -> Vec<(StartByte, EndByte)> {
+        let mut tokens = vec![];
+
+        for (start, end) in tokenizer.tokenize(text) {
+            let mut token_start = start;
+
+            for cut in cuts.iter().filter(|cut| start < **cut && **cut < end) {
+                tokens.push((token_start, *cut));
+                token_start = *cut;
+            }
+
+            tokens.push((token_start, end));
+        }
+
+        tokens
+    }
+
+    /// Encodes a layer of non overlapping spans in BIOES
+    fn encode_layer(
+        text: &Cow<'a, str>,
+        tokens: &[(StartByte, EndByte)],
+        spans: &[TaggedContent<'a>],
+    ) -> Vec<BIOESTag<'a>> {
+        let mut bioes_tags = tokens
+            .iter()
+            .map(|(start, end)| bioes_o![sub_str(text, *start, *end), [*start => *end - 1]])
+            .collect::<Vec<BIOESTag>>();
+
+        for span in spans {
+            let inner_tokens = tokens
+                .iter()
+                .enumerate()
+                .filter(|(_, (start, end))| *start >= span.start && *end <= span.end + 1)
+                .map(|(index, _)| index)
+                .collect::<Vec<usize>>();
+
+            for (position, index) in inner_tokens.iter().enumerate() {
+                let (start, end) = tokens[*index];
+                let content = TaggedContent::new(
+                    sub_str(text, start, end),
+                    start,
+                    end - 1,
+                    span.class.clone(),
+                );
+
+                bioes_tags[*index] = match (position, inner_tokens.len()) {
+                    (_, 1) => bioes_s!(content),
+                    (0, _) => bioes_b!(content),
+                    (p, len) if p == len - 1 => bioes_e!(content),
+                    _ => bioes_i!(content),
+                };
+            }
+        }
+
+        bioes_tags
+    }
+}
+
+impl<'a> NestedBIOES<'a> {
+    /// Encodes nested tags in nested BIOES. The text is split into tokens by the given tokenizer
+    pub fn from_tags(nested_tags: NestedTags<'a>, tokenizer: &dyn Tokenizer) -> NestedBIOES<'a> {
+        let mut cuts = nested_tags
+            .spans()
+            .iter()
+            .flat_map(|span| vec![span.start, span.end + 1])
+            .collect::<Vec<usize>>();
+
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        let tokens = NestedBIOES::tokenize(&nested_tags.text, &cuts, tokenizer);
+
+        let mut layers = nested_tags
+            .layers
+            .iter()
+            .map(|spans| BIOES::new(NestedBIOES::encode_layer(&nested_tags.text, &tokens, spans)))
+            .collect::<Vec<BIOES>>();
+
+        // A text without any match is still represented by a single layer of outside tags
+        if layers.is_empty() {
+            layers.push(BIOES::new(NestedBIOES::encode_layer(
+                &nested_tags.text,
+                &tokens,
+                &[],
+            )));
+        }
+
+        NestedBIOES { layers }
+    }
+}
+
+/// Encodes nested tags in nested BIOES, tokens are separated by whitespaces
+impl<'a> From<NestedTags<'a>> for NestedBIOES<'a> {
+    fn from(nested_tags: NestedTags<'a>) -> Self {
+        NestedBIOES::from_tags(nested_tags, &WhitespaceTokenizer)
+    }
+}
+
+impl NestedBIOES<'_> {
+    /// Returns the rows of the nested format: a token followed by one tag per layer
+    fn rows(&self) -> Vec<(&BIOESTag<'_>, Vec<&BIOESTag<'_>>)> {
+        match self.layers.first() {
+            Some(first_layer) => first_layer
+                .tags()
+                .iter()
+                .enumerate()
+                .map(|(index, tag)| {
+                    let labels = self
+                        .layers
+                        .iter()
+                        .map(|layer| &layer.tags()[index])
+                        .collect::<Vec<&BIOESTag>>();
+                    (tag, labels)
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+impl fmt::Display for NestedBIOES<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let results = self
+            .rows()
+            .iter()
+            .map(|(token, labels)| {
+                let labels = labels.iter().map(|l| l.label()).collect::<Vec<String>>();
+                format!("{} {}", token.text().trim(), labels.join(" "))
+            })
+            .collect::<Vec<String>>();
+        write!(f, "{}", results.join("\n"))
+    }
+}
+
+impl PrettyDisplay for NestedBIOES<'_> {
+    fn pretty_display(&self) -> String {
+        let results = self
+            .rows()
+            .iter()
+            .map(|(token, labels)| {
+                let labels = labels
+                    .iter()
+                    .map(|l| match l {
+                        BIOESTag::Outside(_) => l.label().dimmed().to_string(),
+                        _ => l.label().bold().to_string(),
+                    })
+                    .collect::<Vec<String>>();
+
+                format!(
+                    "{:<55} {:<} {}",
+                    token.text().trim().bold(),
+                    " ▍".dimmed(),
+                    labels.join(" ")
+                )
+            })
+            .collect::<Vec<String>>();
+        results.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_share_tokens() {
+        let text = "au Centre Léon Bérard de Lyon";
+        let span = |term: &str, class: &'static str| {
+            let start = text.find(term).unwrap();
+            TaggedContent::new(
+                &text[start..start + term.len()],
+                start,
+                start + term.len() - 1,
+                class,
+            )
+        };
+        let nested = NestedTags::new(
+            text,
+            vec![
+                span("Léon Bérard", "PER"),
+                span("Centre Léon Bérard de Lyon", "ORG"),
+                span("Lyon", "LOC"),
+            ],
+        );
+
+        assert_eq!(nested.layers.len(), 2);
+        assert_eq!(
+            NestedBIOES::from(nested).to_string(),
+            [
+                "au O O",
+                "Centre B-ORG O",
+                "Léon I-ORG B-PER",
+                "Bérard I-ORG E-PER",
+                "de I-ORG O",
+                "Lyon E-ORG S-LOC",
+            ]
+            .join("\n")
+        );
+    }
+}
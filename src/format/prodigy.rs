@@ -0,0 +1,123 @@
+use crate::format::{char_spans, AnnotatedText};
+use crate::tagger::Tags;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Prodigy task with spans (JSONL): `{"text": ..., "spans": [{"start", "end", "label", "text"}]}`.
+/// Offsets are character offsets with an exclusive end.
+/// Rejected and ignored answers of an export are skipped
+pub struct Prodigy<'a> {
+    text: Cow<'a, str>,
+    #[serde(default)]
+    spans: Vec<ProdigySpan<'a>>,
+    #[serde(default, skip_serializing)]
+    answer: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A span of a Prodigy task
+pub struct ProdigySpan<'a> {
+    start: usize,
+    end: usize,
+    label: Cow<'a, str>,
+    #[serde(default)]
+    text: Option<Cow<'a, str>>,
+}
+
+impl<'a> Prodigy<'a> {
+    /// Builds the Prodigy task of a tagged text
+    pub fn from_tags(text: &'a str, tags: &Tags<'a>) -> Prodigy<'a> {
+        let spans = char_spans(text, tags)
+            .into_iter()
+            .map(|(start, end, span)| ProdigySpan {
+                start,
+                end,
+                label: span.class.clone(),
+                text: Some(span.original_text.clone()),
+            })
+            .collect();
+
+        Prodigy {
+            text: Cow::Borrowed(text),
+            spans,
+            answer: None,
+        }
+    }
+
+    /// Reads the accepted tasks of a Prodigy JSONL export
+    pub fn read(input: &str) -> Result<Vec<AnnotatedText>, Box<dyn Error>> {
+        let mut texts = vec![];
+
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            let task: Prodigy = serde_json::from_str(line)?;
+
+            if let Some("reject") | Some("ignore") = task.answer.as_deref() {
+                continue;
+            }
+
+            let spans = task
+                .spans
+                .into_iter()
+                .map(|span| (span.start, span.end, span.label.into_owned()))
+                .collect();
+
+            texts.push(AnnotatedText::from_char_spans(
+                task.text.into_owned(),
+                spans,
+            )?);
+        }
+
+        Ok(texts)
+    }
+}
+
+/// Prints the task on a single line
+impl fmt::Display for Prodigy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::{Tag, TaggedContent};
+
+    #[test]
+    fn accepted_tasks_are_read_back_into_tags() {
+        let text = "À Saint-Étienne";
+        let spans = [TaggedContent::new("Saint-Étienne", 3, 16, "LOC")];
+        let tags = Tags::from_spans(text, &spans);
+
+        let task = Prodigy::from_tags(text, &tags).to_string();
+        assert_eq!(
+            task,
+            r#"{"text":"À Saint-Étienne","spans":[{"start":2,"end":15,"label":"LOC","text":"Saint-Étienne"}]}"#
+        );
+
+        let export = [
+            format!(r#"{},"answer":"accept"}}"#, &task[..task.len() - 1]),
+            r#"{"text":"Lyon","spans":[{"start":0,"end":4,"label":"PER"}],"answer":"reject"}"#
+                .into(),
+            r#"{"text":"Paris","answer":"ignore"}"#.into(),
+            task,
+        ]
+        .join("\n");
+
+        let texts = Prodigy::read(&export).unwrap();
+        assert_eq!(texts.len(), 2);
+
+        for text in &texts {
+            match &text.tags().0[..] {
+                [Tag::UnTagged(_), Tag::Tagged(span)] => {
+                    assert_eq!((span.start, span.end, span.class.as_ref()), (3, 16, "LOC"))
+                }
+                _ => panic!("Saint-Étienne isn't tagged"),
+            }
+        }
+    }
+}
@@ -0,0 +1,526 @@
+use crate::errors::{InvalidAnnotation, InvalidScheme};
+use crate::format::{BIOESTag, IOBTag, WhitespaceTokenizer, BIOES, IOB};
+use crate::tagger::{Tag, TaggedContent, Tags, UntaggedContent};
+use crate::traits::{PrettyDisplay, Tokenizer};
+use crate::types::*;
+use colored::{Color, Colorize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Sequence-labeling schemes. Every scheme encodes entities as prefixed labels (e.g. `B-LOC`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    /// Inside Outside: entity tokens are `I`. Adjacent entities of the same class can't be told apart
+    IO,
+    /// Strict IOB: entities are `I`, `B` only marks an entity following an entity of the same class
+    IOB1,
+    /// IOB: entities begin with `B`, followed by `I`
+    IOB2,
+    /// Entities are `I`, `E` only marks an entity preceding an entity of the same class
+    IOE1,
+    /// Entities end with `E`, preceded by `I`
+    IOE2,
+    /// Begin Inside End Single
+    BIOES,
+    /// Begin Inside Last Unit
+    BILOU,
+}
+
+impl Scheme {
+    /// Returns the prefixes of entity labels, in the order of label ids
+    pub fn prefixes(&self) -> &'static [char] {
+        match self {
+            Scheme::IO => &['I'],
+            Scheme::IOB1 | Scheme::IOB2 => &['B', 'I'],
+            Scheme::IOE1 | Scheme::IOE2 => &['I', 'E'],
+            Scheme::BIOES => &['B', 'I', 'E', 'S'],
+            Scheme::BILOU => &['B', 'I', 'L', 'U'],
+        }
+    }
+
+    /// Returns the prefix of a token of an entity, given its position in the entity and
+    /// whether the entity touches entities of the same class
+    fn prefix(
+        &self,
+        position: usize,
+        length: usize,
+        follows_same_class: bool,
+        precedes_same_class: bool,
+    ) -> char {
+        let first = position == 0;
+        let last = position + 1 == length;
+
+        match self {
+            Scheme::IO => 'I',
+            Scheme::IOB1 if first && follows_same_class => 'B',
+            Scheme::IOB1 => 'I',
+            Scheme::IOB2 if first => 'B',
+            Scheme::IOB2 => 'I',
+            Scheme::IOE1 if last && precedes_same_class => 'E',
+            Scheme::IOE1 => 'I',
+            Scheme::IOE2 if last => 'E',
+            Scheme::IOE2 => 'I',
+            Scheme::BIOES if first && last => 'S',
+            Scheme::BILOU if first && last => 'U',
+            Scheme::BIOES | Scheme::BILOU if first => 'B',
+            Scheme::BIOES if last => 'E',
+            Scheme::BILOU if last => 'L',
+            Scheme::BIOES | Scheme::BILOU => 'I',
+        }
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = InvalidScheme;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "io" => Ok(Scheme::IO),
+            "iob1" => Ok(Scheme::IOB1),
+            "iob" | "iob2" => Ok(Scheme::IOB2),
+            "ioe1" => Ok(Scheme::IOE1),
+            "ioe" | "ioe2" => Ok(Scheme::IOE2),
+            "bioes" | "iobes" => Ok(Scheme::BIOES),
+            "bilou" => Ok(Scheme::BILOU),
+            _ => Err(InvalidScheme(input.to_string())),
+        }
+    }
+}
+
+/// Tags of a sequence-labeling scheme
+#[derive(Debug, Clone)]
+pub enum SchemeTag<'a> {
+    /// A token of an entity and its prefix
+    Entity(char, TaggedContent<'a>),
+    Outside(UntaggedContent<'a>),
+}
+
+impl SchemeTag<'_> {
+    /// Returns the label of a tag (e.g. `B-LOC`, `O`)
+    pub fn label(&self) -> String {
+        match self {
+            Self::Entity(prefix, tag) => format!("{}-{}", prefix, tag.class),
+            Self::Outside(_) => "O".to_string(),
+        }
+    }
+
+    /// Returns the text of a tag
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Entity(_, tag) => &tag.original_text,
+            Self::Outside(tag) => &tag.original_text,
+        }
+    }
+
+    /// Returns the position of a tag in the text: first byte and last byte (inclusive end)
+    pub fn span(&self) -> (StartByte, EndByte) {
+        match self {
+            Self::Entity(_, tag) => (tag.start, tag.end),
+            Self::Outside(tag) => (tag.start, tag.end),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Tokens labeled in any sequence-labeling scheme
+pub struct SchemeTags<'a> {
+    scheme: Scheme,
+    tags: Vec<SchemeTag<'a>>,
+}
+
+impl<'a> SchemeTags<'a> {
+    /// Encodes tags in a scheme. Each tag is split into tokens by the given tokenizer
+    pub fn from_tags(tags: Tags<'a>, scheme: Scheme, tokenizer: &dyn Tokenizer) -> SchemeTags<'a> {
+        let mut tokens = vec![];
+        let mut entities = vec![];
+
+        for tag in tags.0 {
+            match tag {
+                Tag::Tagged(t) => {
+                    let entity_tokens = t.tokens(tokenizer);
+
+                    if !entity_tokens.is_empty() {
+                        entities.push((tokens.len(), tokens.len() + entity_tokens.len() - 1));
+                    }
+
+                    tokens.extend(entity_tokens.into_iter().map(Tag::Tagged));
+                }
+                Tag::UnTagged(t) => {
+                    tokens.extend(t.tokens(tokenizer).into_iter().map(Tag::UnTagged));
+                }
+            }
+        }
+
+        SchemeTags::encode(tokens, &entities, scheme)
+    }
+
+    /// Reads sentences of labeled tokens: one token per line, the token in the first column and
+    /// its label in the last one (e.g. `Lyon B-LOC`), sentences separated by blank lines.
+    /// Token positions are byte positions in the input
+    pub fn read(input: &'a str, scheme: Scheme) -> Result<Vec<SchemeTags<'a>>, InvalidAnnotation> {
+        let mut sentences = vec![];
+        let mut tags = vec![];
+        let mut start = 0;
+
+        for line in input.split_inclusive('\n') {
+            let line_start = start;
+            start += line.len();
+
+            let mut columns = line.split_whitespace();
+            let (token, label) = match (columns.next(), columns.last()) {
+                (Some(token), Some(label)) => (token, label),
+                (Some(token), None) => {
+                    return Err(InvalidAnnotation(format!("token {:?} has no label", token)))
+                }
+                (None, _) => {
+                    if !tags.is_empty() {
+                        sentences.push(SchemeTags {
+                            scheme,
+                            tags: std::mem::take(&mut tags),
+                        });
+                    }
+                    continue;
+                }
+            };
+
+            let token_start = line_start + line.find(token).unwrap_or_default();
+            let token_end = token_start + token.len() - 1;
+
+            let tag = match label.split_once('-') {
+                None if label == "O" => {
+                    SchemeTag::Outside(UntaggedContent::new(token, token_start, token_end))
+                }
+                Some((prefix, class)) if !class.is_empty() => match prefix.parse::<char>() {
+                    Ok(prefix) if scheme.prefixes().contains(&prefix) => SchemeTag::Entity(
+                        prefix,
+                        TaggedContent::new(token, token_start, token_end, class),
+                    ),
+                    _ => {
+                        return Err(InvalidAnnotation(format!(
+                            "label {} of token {:?} isn't a {:?} label",
+                            label, token, scheme
+                        )))
+                    }
+                },
+                _ => {
+                    return Err(InvalidAnnotation(format!(
+                        "label {} of token {:?} isn't a {:?} label",
+                        label, token, scheme
+                    )))
+                }
+            };
+
+            tags.push(tag);
+        }
+
+        if !tags.is_empty() {
+            sentences.push(SchemeTags { scheme, tags });
+        }
+
+        Ok(sentences)
+    }
+
+    /// Converts the tags to another scheme. Converting to IO merges adjacent entities of
+    /// the same class, every other conversion is lossless
+    pub fn convert(self, scheme: Scheme) -> SchemeTags<'a> {
+        let entities = self.entities();
+
+        let tokens = self
+            .tags
+            .into_iter()
+            .map(|tag| match tag {
+                SchemeTag::Entity(_, t) => Tag::Tagged(t),
+                SchemeTag::Outside(t) => Tag::UnTagged(t),
+            })
+            .collect();
+
+        SchemeTags::encode(tokens, &entities, scheme)
+    }
+
+    /// Returns the scheme of the tags
+    #[allow(dead_code)]
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    /// Returns the tags of the sequence
+    pub fn tags(&self) -> &[SchemeTag<'a>] {
+        &self.tags
+    }
+
+    /// Returns the entities of the sequence: first and last token indexes.
+    /// An entity starts on a `B`, `S` or `U` prefix, after an `E`, `L`, `S` or `U` prefix
+    /// or after a token which isn't in an entity of the same class
+    pub fn entities(&self) -> Vec<(usize, usize)> {
+        let mut entities: Vec<(usize, usize)> = vec![];
+        let mut previous: Option<(char, &str)> = None;
+
+        for (index, tag) in self.tags.iter().enumerate() {
+            match tag {
+                SchemeTag::Entity(prefix, t) => {
+                    let continues = match previous {
+                        Some((previous_prefix, previous_class)) => {
+                            previous_class == t.class
+                                && !matches!(prefix, 'B' | 'S' | 'U')
+                                && !matches!(previous_prefix, 'E' | 'L' | 'S' | 'U')
+                        }
+                        None => false,
+                    };
+
+                    match entities.last_mut() {
+                        Some(entity) if continues => entity.1 = index,
+                        _ => entities.push((index, index)),
+                    }
+
+                    previous = Some((*prefix, &t.class));
+                }
+                SchemeTag::Outside(_) => previous = None,
+            }
+        }
+
+        entities
+    }
+
+    /// Labels tokens, given the first and last token indexes of each entity
+    fn encode(tokens: Vec<Tag<'a>>, entities: &[(usize, usize)], scheme: Scheme) -> SchemeTags<'a> {
+        let mut prefixes = vec!['O'; tokens.len()];
+        let class = |index: usize| match &tokens[index] {
+            Tag::Tagged(t) => Some(&t.class),
+            Tag::UnTagged(_) => None,
+        };
+
+        for (position, (first, last)) in entities.iter().enumerate() {
+            let follows_same_class = position > 0
+                && entities[position - 1].1 + 1 == *first
+                && class(*first - 1) == class(*first);
+            let precedes_same_class = entities
+                .get(position + 1)
+                .is_some_and(|next| *last + 1 == next.0 && class(next.0) == class(*last));
+
+            for (position, prefix) in prefixes[*first..=*last].iter_mut().enumerate() {
+                *prefix = scheme.prefix(
+                    position,
+                    last - first + 1,
+                    follows_same_class,
+                    precedes_same_class,
+                );
+            }
+        }
+
+        let tags = tokens
+            .into_iter()
+            .zip(prefixes)
+            .map(|(token, prefix)| match token {
+                Tag::Tagged(t) => SchemeTag::Entity(prefix, t),
+                Tag::UnTagged(t) => SchemeTag::Outside(t),
+            })
+            .collect();
+
+        SchemeTags { scheme, tags }
+    }
+}
+
+/// Encodes tags in a scheme, tokens are separated by whitespaces
+impl<'a> From<(Tags<'a>, Scheme)> for SchemeTags<'a> {
+    fn from((tags, scheme): (Tags<'a>, Scheme)) -> Self {
+        SchemeTags::from_tags(tags, scheme, &WhitespaceTokenizer)
+    }
+}
+
+impl<'a> From<IOB<'a>> for SchemeTags<'a> {
+    fn from(iob: IOB<'a>) -> Self {
+        let tags = iob
+            .tags()
+            .iter()
+            .cloned()
+            .map(|tag| match tag {
+                IOBTag::Beginning(t) => SchemeTag::Entity('B', t),
+                IOBTag::Inside(t) => SchemeTag::Entity('I', t),
+                IOBTag::Outside(t) => SchemeTag::Outside(t),
+            })
+            .collect();
+
+        SchemeTags {
+            scheme: Scheme::IOB2,
+            tags,
+        }
+    }
+}
+
+impl<'a> From<BIOES<'a>> for SchemeTags<'a> {
+    fn from(bioes: BIOES<'a>) -> Self {
+        let tags = bioes
+            .tags()
+            .iter()
+            .cloned()
+            .map(|tag| match tag {
+                BIOESTag::Beginning(t) => SchemeTag::Entity('B', t),
+                BIOESTag::Inside(t) => SchemeTag::Entity('I', t),
+                BIOESTag::End(t) => SchemeTag::Entity('E', t),
+                BIOESTag::Single(t) => SchemeTag::Entity('S', t),
+                BIOESTag::Outside(t) => SchemeTag::Outside(t),
+            })
+            .collect();
+
+        SchemeTags {
+            scheme: Scheme::BIOES,
+            tags,
+        }
+    }
+}
+
+impl fmt::Display for SchemeTag<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.text().trim(), self.label())
+    }
+}
+
+impl PrettyDisplay for SchemeTag<'_> {
+    fn pretty_display(&self) -> String {
+        match self {
+            Self::Entity(prefix, tag) => {
+                let color = match prefix {
+                    'B' => Color::Green,
+                    'E' | 'L' => Color::Blue,
+                    'S' | 'U' => Color::Magenta,
+                    _ => Color::Cyan,
+                };
+
+                format!(
+                    "{:<55} {:<} {}{}",
+                    tag.original_text.trim().bold(),
+                    " ▍".color(color),
+                    format!("{}-", prefix).color(color).bold(),
+                    tag.class.color(color).bold()
+                )
+            }
+            Self::Outside(tag) => format!(
+                "{:<55} {:<} {}",
+                tag.original_text.trim(),
+                " ▍".dimmed(),
+                "O".dimmed()
+            ),
+        }
+    }
+}
+
+impl fmt::Display for SchemeTags<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let results = self
+            .tags
+            .iter()
+            .map(|t| format!("{}", t))
+            .collect::<Vec<String>>();
+        write!(f, "{}", results.join("\n"))
+    }
+}
+
+impl PrettyDisplay for SchemeTags<'_> {
+    fn pretty_display(&self) -> String {
+        let results = self
+            .tags
+            .iter()
+            .map(|t| t.pretty_display())
+            .collect::<Vec<_>>();
+        results.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Lyon Paris Rome et Marie Curie à Lyon";
+
+    fn tags() -> Tags<'static> {
+        let spans = [
+            (0, "Lyon", "LOC"),
+            (5, "Paris Rome", "LOC"),
+            (19, "Marie Curie", "PER"),
+        ]
+        .iter()
+        .map(|(start, term, class)| {
+            TaggedContent::new(*term, *start, start + term.len() - 1, *class)
+        })
+        .collect::<Vec<TaggedContent>>();
+
+        Tags::from_spans(TEXT, &spans)
+    }
+
+    fn labels(tags: &SchemeTags) -> String {
+        tags.tags()
+            .iter()
+            .map(|tag| tag.label())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn schemes_encode_adjacent_entities() {
+        let expected = [
+            (Scheme::IO, "I-LOC I-LOC I-LOC O I-PER I-PER O O"),
+            (Scheme::IOB1, "I-LOC B-LOC I-LOC O I-PER I-PER O O"),
+            (Scheme::IOB2, "B-LOC B-LOC I-LOC O B-PER I-PER O O"),
+            (Scheme::IOE1, "E-LOC I-LOC I-LOC O I-PER I-PER O O"),
+            (Scheme::IOE2, "E-LOC I-LOC E-LOC O I-PER E-PER O O"),
+            (Scheme::BIOES, "S-LOC B-LOC E-LOC O B-PER E-PER O O"),
+            (Scheme::BILOU, "U-LOC B-LOC L-LOC O B-PER L-PER O O"),
+        ];
+
+        for (scheme, labels_of_scheme) in expected.iter() {
+            assert_eq!(
+                labels(&SchemeTags::from((tags(), *scheme))),
+                *labels_of_scheme
+            );
+        }
+    }
+
+    #[test]
+    fn conversions_are_lossless() {
+        let schemes = [
+            Scheme::IOB1,
+            Scheme::IOB2,
+            Scheme::IOE1,
+            Scheme::IOE2,
+            Scheme::BIOES,
+            Scheme::BILOU,
+        ];
+
+        for from in schemes.iter() {
+            for to in schemes.iter() {
+                let converted = SchemeTags::from((tags(), *from)).convert(*to);
+                assert_eq!(labels(&converted), labels(&SchemeTags::from((tags(), *to))));
+            }
+        }
+
+        let from_bioes = SchemeTags::from(BIOES::from(tags())).convert(Scheme::IOB1);
+        assert_eq!(
+            labels(&from_bioes),
+            labels(&SchemeTags::from((tags(), Scheme::IOB1)))
+        );
+    }
+
+    #[test]
+    fn labeled_tokens_are_read_and_converted() {
+        let input = "Lyon I-LOC\nParis B-LOC\nRome I-LOC\n\nMarie I-PER\nCurie I-PER\nà O\n";
+        let sentences = SchemeTags::read(input, Scheme::IOB1).unwrap();
+
+        assert_eq!(sentences.len(), 2);
+        assert_eq!(sentences[0].scheme(), Scheme::IOB1);
+        assert_eq!(sentences[1].tags()[1].span(), (47, 51));
+        assert_eq!(sentences[1].tags()[1].text(), "Curie");
+
+        let converted = sentences
+            .into_iter()
+            .map(|sentence| labels(&sentence.convert(Scheme::BILOU)))
+            .collect::<Vec<String>>();
+        assert_eq!(converted, ["U-LOC B-LOC L-LOC", "B-PER L-PER O"]);
+    }
+
+    #[test]
+    fn labels_of_other_schemes_are_rejected() {
+        assert!(SchemeTags::read("Lyon S-LOC\n", Scheme::IOB2).is_err());
+        assert!(SchemeTags::read("Lyon LOC\n", Scheme::IOB2).is_err());
+        assert!(SchemeTags::read("Lyon\n", Scheme::IOB2).is_err());
+    }
+}
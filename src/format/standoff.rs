@@ -0,0 +1,108 @@
+use crate::tagger::{NestedTags, OffsetConverter, Offsets, TaggedContent};
+use crate::traits::PrettyDisplay;
+use colored::Colorize;
+use std::borrow::Cow;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Standoff format: one line per span (start, end, class, text). Spans may overlap.
+/// Approximate matches have two more columns: the dictionary term and the edit distance.
+/// Matches dropped because of their capitalization are reported as comment lines.
+/// Offsets are inclusive byte offsets unless other offsets are set
+pub struct Standoff<'a> {
+    text: Cow<'a, str>,
+    spans: Vec<TaggedContent<'a>>,
+    dropped: Vec<TaggedContent<'a>>,
+    offsets: Offsets,
+}
+
+impl<'a> From<NestedTags<'a>> for Standoff<'a> {
+    fn from(nested_tags: NestedTags<'a>) -> Self {
+        Standoff {
+            spans: nested_tags.spans().into_iter().cloned().collect(),
+            dropped: nested_tags.dropped,
+            text: nested_tags.text,
+            offsets: Offsets::default(),
+        }
+    }
+}
+
+impl Standoff<'_> {
+    /// Sets the unit and the end convention of reported offsets
+    pub fn with_offsets(mut self, offsets: Offsets) -> Self {
+        self.offsets = offsets;
+        self
+    }
+}
+
+impl fmt::Display for Standoff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let converter = OffsetConverter::new(&self.text, self.offsets);
+
+        let results = self
+            .spans
+            .iter()
+            .map(|span| (span, converter.span(span.start, span.end)))
+            .map(|(span, (start, end))| match &span.fuzzy {
+                Some(fuzzy) => format!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    start, end, span.class, span.original_text, fuzzy.term, fuzzy.distance
+                ),
+                None => format!("{}\t{}\t{}\t{}", start, end, span.class, span.original_text),
+            })
+            .chain(self.dropped.iter().map(|span| {
+                let (start, end) = converter.span(span.start, span.end);
+
+                format!(
+                    "# dropped (capitalization)\t{}\t{}\t{}\t{}",
+                    start, end, span.class, span.original_text
+                )
+            }))
+            .collect::<Vec<String>>();
+        write!(f, "{}", results.join("\n"))
+    }
+}
+
+impl PrettyDisplay for Standoff<'_> {
+    fn pretty_display(&self) -> String {
+        let converter = OffsetConverter::new(&self.text, self.offsets);
+
+        let results = self
+            .spans
+            .iter()
+            .map(|span| {
+                let (start, end) = converter.span(span.start, span.end);
+                let fuzzy = match &span.fuzzy {
+                    Some(fuzzy) => format!("≈ {} ({})", fuzzy.term, fuzzy.distance)
+                        .yellow()
+                        .to_string(),
+                    None => String::new(),
+                };
+
+                format!(
+                    "{:>6} {:>6} {:<} {:<12} {} {}",
+                    start.to_string().dimmed(),
+                    end.to_string().dimmed(),
+                    " ▍".purple(),
+                    span.class.purple().bold(),
+                    span.original_text.bold(),
+                    fuzzy
+                )
+            })
+            .chain(self.dropped.iter().map(|span| {
+                let (start, end) = converter.span(span.start, span.end);
+
+                format!(
+                    "{:>6} {:>6} {:<} {:<12} {} {}",
+                    start.to_string().dimmed(),
+                    end.to_string().dimmed(),
+                    " ▍".dimmed(),
+                    span.class.dimmed(),
+                    span.original_text.dimmed().strikethrough(),
+                    "(dropped: capitalization)".dimmed()
+                )
+            }))
+            .collect::<Vec<String>>();
+        results.join("\n")
+    }
+}
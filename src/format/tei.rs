@@ -0,0 +1,201 @@
+use crate::tagger::{Tag, Tagger, Tags};
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::{Reader, Writer};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+
+/// Elements whose content is never annotated: the header and existing annotations
+const SKIPPED_ELEMENTS: [&[u8]; 6] = [
+    b"teiHeader",
+    b"persName",
+    b"placeName",
+    b"orgName",
+    b"rs",
+    b"name",
+];
+
+/// Maps classes to TEI elements. Classes without element are encoded as `<rs type="CLASS">`
+#[derive(Debug, Clone)]
+pub struct TeiMapping {
+    elements: HashMap<String, String>,
+}
+
+impl Default for TeiMapping {
+    fn default() -> Self {
+        let elements = [
+            ("PER", "persName"),
+            ("LOC", "placeName"),
+            ("ORG", "orgName"),
+        ]
+        .iter()
+        .map(|(class, element)| (class.to_string(), element.to_string()))
+        .collect();
+
+        TeiMapping { elements }
+    }
+}
+
+impl TeiMapping {
+    /// Encodes a class as the given element
+    pub fn element<S: Into<String>>(mut self, class: S, element: S) -> TeiMapping {
+        self.elements.insert(class.into(), element.into());
+        self
+    }
+
+    /// Wraps the tagged spans of a text in TEI elements. The text is escaped.
+    /// Spans holding an identifier get a `ref` attribute
+    pub fn encode(&self, tags: &Tags) -> String {
+        let mut encoded = String::new();
+
+        for tag in &tags.0 {
+            match tag {
+                Tag::Tagged(span) => {
+                    let element = self
+                        .elements
+                        .get(span.class.as_ref())
+                        .map_or("rs", |element| element.as_str());
+
+                    encoded.push('<');
+                    encoded.push_str(element);
+
+                    if element == "rs" {
+                        encoded.push_str(&format!(" type=\"{}\"", escape(&span.class)));
+                    }
+
+                    if let Some(identifier) = span
+                        .entry
+                        .as_ref()
+                        .and_then(|entry| entry.identifier.as_ref())
+                    {
+                        encoded.push_str(&format!(" ref=\"{}\"", escape(identifier)));
+                    }
+
+                    encoded.push_str(&format!(">{}</{}>", escape(&span.original_text), element));
+                }
+                Tag::UnTagged(content) => encoded.push_str(&escape(&content.original_text)),
+            }
+        }
+
+        encoded
+    }
+
+    /// Annotates the text nodes of an existing TEI document. The header and existing
+    /// annotations are left untouched, as is the markup: spans never cross elements
+    pub fn annotate(&self, document: &str, tagger: &Tagger) -> Result<String, Box<dyn Error>> {
+        let mut reader = Reader::from_str(document);
+        let mut writer = Writer::new(Vec::new());
+        let mut skipped_depth: usize = 0;
+        let mut in_text = false;
+
+        loop {
+            match reader.read_event()? {
+                Event::Eof => break,
+                Event::Start(element) => {
+                    let name = element.local_name();
+
+                    if skipped_depth > 0 || SKIPPED_ELEMENTS.contains(&name.as_ref()) {
+                        skipped_depth += 1;
+                    }
+
+                    in_text |= name.as_ref() == b"text";
+                    writer.write_event(Event::Start(element))?;
+                }
+                Event::End(element) => {
+                    skipped_depth = skipped_depth.saturating_sub(1);
+
+                    if element.local_name().as_ref() == b"text" {
+                        in_text = false;
+                    }
+
+                    writer.write_event(Event::End(element))?;
+                }
+                Event::Text(text) if in_text && skipped_depth == 0 => {
+                    let content = text.unescape()?;
+                    let tags = tagger.tag(&content);
+
+                    match tags.0.iter().any(|tag| matches!(tag, Tag::Tagged(_))) {
+                        true => writer.get_mut().write_all(self.encode(&tags).as_bytes())?,
+                        false => writer.write_event(Event::Text(text))?,
+                    }
+                }
+                event => writer.write_event(event)?,
+            }
+        }
+
+        Ok(String::from_utf8(writer.into_inner())?)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// TEI document: one paragraph per tagged line, matched spans are wrapped in TEI elements
+/// (`<persName>`, `<placeName>`, `<orgName>`, `<rs type="CLASS">`)
+pub struct Tei<'a> {
+    title: Cow<'a, str>,
+    paragraphs: Vec<String>,
+}
+
+impl<'a> Tei<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(title: S) -> Tei<'a> {
+        Tei {
+            title: title.into(),
+            paragraphs: vec![],
+        }
+    }
+
+    /// Adds a paragraph holding the tagged text
+    pub fn push(&mut self, tags: &Tags, mapping: &TeiMapping) {
+        self.paragraphs.push(mapping.encode(tags));
+    }
+}
+
+impl fmt::Display for Tei<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(f, r#"<TEI xmlns="http://www.tei-c.org/ns/1.0">"#)?;
+        writeln!(f, "  <teiHeader>")?;
+        writeln!(f, "    <fileDesc>")?;
+        writeln!(
+            f,
+            "      <titleStmt><title>{}</title></titleStmt>",
+            escape(&self.title)
+        )?;
+        writeln!(f, "      <publicationStmt><p/></publicationStmt>")?;
+        writeln!(f, "      <sourceDesc><p/></sourceDesc>")?;
+        writeln!(f, "    </fileDesc>")?;
+        writeln!(f, "  </teiHeader>")?;
+        writeln!(f, "  <text>")?;
+        writeln!(f, "    <body>")?;
+
+        for paragraph in &self.paragraphs {
+            writeln!(f, "      <p>{}</p>", paragraph)?;
+        }
+
+        writeln!(f, "    </body>")?;
+        writeln!(f, "  </text>")?;
+        write!(f, "</TEI>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::TaggedContent;
+
+    #[test]
+    fn spans_are_wrapped_and_text_is_escaped() {
+        let text = "<p> Lyon & Curie";
+        let spans = [
+            TaggedContent::new("Lyon", 4, 7, "LOC").with_entry("Lyon", Some("Q456"), "d.tsv", 0),
+            TaggedContent::new("Curie", 11, 15, "\"X\""),
+        ];
+
+        assert_eq!(
+            TeiMapping::default().encode(&Tags::from_spans(text, &spans)),
+            r#"&lt;p&gt; <placeName ref="Q456">Lyon</placeName> &amp; <rs type="&quot;X&quot;">Curie</rs>"#
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use crate::errors::InvalidTokenizer;
+use crate::traits::Tokenizer;
+use crate::types::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Splits tokens on whitespaces, including non-breaking spaces
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhitespaceTokenizer;
+
+/// Splits tokens on whitespaces and splits punctuation off words, as in CoNLL corpora
+/// ("Paris," gives "Paris" and ","). Hyphens inside words are kept ("Pierre-Bénite")
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PunctuationTokenizer;
+
+/// Splits tokens on Unicode word boundaries (UAX #29), whitespaces are not tokens
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnicodeTokenizer;
+
+/// Returns a tokenizer by its name: `whitespace`, `punctuation` or `unicode`
+pub fn tokenizer(name: &str) -> Result<Box<dyn Tokenizer>, InvalidTokenizer> {
+    match name {
+        "whitespace" => Ok(Box::new(WhitespaceTokenizer)),
+        "punctuation" => Ok(Box::new(PunctuationTokenizer)),
+        "unicode" => Ok(Box::new(UnicodeTokenizer)),
+        _ => Err(InvalidTokenizer(name.to_string())),
+    }
+}
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokenize(&self, input: &str) -> Vec<(StartByte, EndByte)> {
+        let mut tokens = vec![];
+        let mut token_start: Option<usize> = None;
+
+        for (index, c) in input.char_indices() {
+            match (token_start, c.is_whitespace()) {
+                (Some(start), true) => {
+                    tokens.push((start, index));
+                    token_start = None;
+                }
+                (None, false) => token_start = Some(index),
+                _ => {}
+            }
+        }
+
+        if let Some(start) = token_start {
+            tokens.push((start, input.len()));
+        }
+
+        tokens
+    }
+}
+
+/// Returns true for ASCII punctuation and common Unicode quotes, dashes and ellipsis
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || matches!(c, '«' | '»' | '‘' | '’' | '“' | '”' | '–' | '—' | '…')
+}
+
+impl Tokenizer for PunctuationTokenizer {
+    fn tokenize(&self, input: &str) -> Vec<(StartByte, EndByte)> {
+        let mut tokens = vec![];
+        let mut token_start: Option<usize> = None;
+        let mut chars = input.char_indices().peekable();
+        let mut previous: Option<char> = None;
+
+        while let Some((index, c)) = chars.next() {
+            let next = chars.peek().map(|(_, next)| *next);
+            let inner_hyphen = c == '-'
+                && previous.is_some_and(char::is_alphanumeric)
+                && next.is_some_and(char::is_alphanumeric);
+
+            if c.is_whitespace() || (is_punctuation(c) && !inner_hyphen) {
+                if let Some(start) = token_start.take() {
+                    tokens.push((start, index));
+                }
+
+                if !c.is_whitespace() {
+                    tokens.push((index, index + c.len_utf8()));
+                }
+            } else if token_start.is_none() {
+                token_start = Some(index);
+            }
+
+            previous = Some(c);
+        }
+
+        if let Some(start) = token_start {
+            tokens.push((start, input.len()));
+        }
+
+        tokens
+    }
+}
+
+impl Tokenizer for UnicodeTokenizer {
+    fn tokenize(&self, input: &str) -> Vec<(StartByte, EndByte)> {
+        input
+            .split_word_bound_indices()
+            .filter(|(_, segment)| !segment.chars().all(char::is_whitespace))
+            .map(|(start, segment)| (start, start + segment.len()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the text of each token
+    fn tokens<'t>(tokenizer: &dyn Tokenizer, input: &'t str) -> Vec<&'t str> {
+        tokenizer
+            .tokenize(input)
+            .into_iter()
+            .map(|(start, end)| &input[start..end])
+            .collect()
+    }
+
+    #[test]
+    fn whitespace_tokenizer_splits_on_whitespaces() {
+        assert_eq!(
+            tokens(&WhitespaceTokenizer, " Paris,\u{a0}Pierre-Bénite\tl'Isère "),
+            ["Paris,", "Pierre-Bénite", "l'Isère"]
+        );
+    }
+
+    #[test]
+    fn punctuation_tokenizer_splits_punctuation_off_words() {
+        assert_eq!(tokens(&PunctuationTokenizer, "Paris,"), ["Paris", ","]);
+        assert_eq!(
+            tokens(&PunctuationTokenizer, "« l'Université » (Lyon 2)…"),
+            ["«", "l", "'", "Université", "»", "(", "Lyon", "2", ")", "…"]
+        );
+    }
+
+    #[test]
+    fn punctuation_tokenizer_keeps_inner_hyphens() {
+        assert_eq!(
+            tokens(&PunctuationTokenizer, "Pierre-Bénite -Lyon- 2-3 – Vaulx"),
+            ["Pierre-Bénite", "-", "Lyon", "-", "2-3", "–", "Vaulx"]
+        );
+    }
+
+    #[test]
+    fn unicode_tokenizer_follows_word_boundaries() {
+        assert_eq!(tokens(&UnicodeTokenizer, "Paris,"), ["Paris", ","]);
+        assert_eq!(
+            tokens(&UnicodeTokenizer, "Pierre-Bénite l'Isère, 3.5 km"),
+            ["Pierre", "-", "Bénite", "l'Isère", ",", "3.5", "km"]
+        );
+    }
+
+    #[test]
+    fn tokenizers_are_found_by_name() {
+        assert!(tokenizer("punctuation").is_ok());
+        assert!(tokenizer("sentencepiece").is_err());
+    }
+}
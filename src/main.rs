@@ -1,9 +1,15 @@
+#![allow(clippy::upper_case_acronyms, clippy::module_inception)]
+
+use clap::ArgMatches;
 use colored::*;
+use errors::{InvalidMatchOptions, InvalidTeiElement, UnsupportedFormat};
+use std::collections::HashSet;
 use std::error::Error;
 use std::path::PathBuf;
 
 #[macro_use]
 mod tagger;
+mod bench;
 mod cli;
 mod dict;
 mod errors;
@@ -11,10 +17,15 @@ mod format;
 mod traits;
 mod types;
 use cli::cli;
-use dict::DictionaryBuilder;
-use format::{BIOES, IOB};
-use tagger::TaggerBuilder;
-use traits::PrettyDisplay;
+use dict::{Capitalization, DictionaryBuilder, MatchOptions};
+use format::{
+    Brat, Conll, ConllOptions, ConllU, Doccano, HtmlReport, HuggingFaceRecord, InlineMarkup,
+    InlineTemplate, JsonRecord, LabelMap, LabelStudio, LabelStudioTask, Mentions, MentionsOptions,
+    NestedBIOES, Prodigy, Scheme, SchemeTags, Sentence, Standoff, TableFormat, Tei, TeiMapping,
+    BIOES, IOB,
+};
+use tagger::{AhoCorasickOptions, Backend, Engine, Offsets, Tagger, TaggerBuilder};
+use traits::{PrettyDisplay, Tokenizer};
 
 use std::fs::File;
 use std::io::Read;
@@ -25,13 +36,91 @@ use aho_corasick::MatchKind;
 use env_logger::Builder;
 use glob::glob;
 use log::LevelFilter;
+use rust_stemmers::Algorithm;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let matches = cli();
 
     if !matches.is_present("silent") {
         let mut log_builder = Builder::new();
-        log_builder.filter(None, LevelFilter::Info).init();
+        let level = match matches.occurrences_of("v") {
+            0 => LevelFilter::Info,
+            1 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        };
+        log_builder.filter(None, level).init();
+    }
+
+    let tokenizer = format::tokenizer(matches.value_of("tokenizer").unwrap_or("whitespace"))?;
+
+    let offsets = Offsets {
+        unit: matches.value_of("offsets").unwrap_or("bytes").parse()?,
+        end: matches
+            .value_of("span_end")
+            .unwrap_or("inclusive")
+            .parse()?,
+    };
+
+    let scheme = matches
+        .value_of("scheme")
+        .unwrap_or("iob2")
+        .parse::<Scheme>()?;
+
+    let conll_options = ConllOptions {
+        columns: matches.value_of("conll_columns").unwrap_or("4").parse()?,
+        delimiter: match matches.value_of("conll_delimiter") {
+            Some("tab") => "\t".to_string(),
+            Some("space") | None => " ".to_string(),
+            Some(delimiter) => delimiter.to_string(),
+        },
+    };
+
+    let mentions_options = MentionsOptions {
+        format: match matches.value_of("mentions_format") {
+            Some("csv") => TableFormat::CSV,
+            _ => TableFormat::TSV,
+        },
+        context: matches
+            .value_of("mentions_context")
+            .unwrap_or("0")
+            .parse()?,
+        offsets,
+    };
+
+    let mut tei_mapping = TeiMapping::default();
+
+    if let Some(elements) = matches.values_of("tei_element") {
+        for element in elements {
+            match element.rsplit_once(':') {
+                Some((class, name)) if !class.is_empty() && !name.is_empty() => {
+                    tei_mapping = tei_mapping.element(class, name)
+                }
+                _ => return Err(Box::new(InvalidTeiElement(element.to_string()))),
+            }
+        }
+    }
+
+    let mut inline_markup = match matches.value_of("inline_style") {
+        Some("markdown") => InlineMarkup::markdown(),
+        _ => InlineMarkup::xml(),
+    };
+
+    if let Some(templates) = matches.values_of("inline_class") {
+        for template in templates.collect::<Vec<&str>>().chunks(3) {
+            inline_markup =
+                inline_markup.class(template[0], InlineTemplate::new(template[1], template[2]));
+        }
+    }
+
+    if let Some(tool) = matches.value_of("import") {
+        return import(
+            &matches,
+            tool,
+            scheme,
+            tokenizer.as_ref(),
+            &conll_options,
+            &inline_markup,
+        );
     }
 
     let dictionary = match matches.values_of("dictionary") {
@@ -43,12 +132,57 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .map(|p| p.expect("Failed to read glob pattern"))
                 .collect::<Vec<PathBuf>>();
 
-            DictionaryBuilder::from_files(&dictionaries)?.build()
+            DictionaryBuilder::from_files(&dictionaries)?.build()?
         }
 
         None => panic!("A dictionary is required"),
     };
 
+    if let Some(bench_matches) = matches.subcommand_matches("bench") {
+        let backends = match bench_matches.values_of("backends") {
+            Some(backends) => backends
+                .map(|backend| backend.parse::<Backend>())
+                .collect::<Result<Vec<Backend>, _>>()?,
+            None => Backend::ALL.to_vec(),
+        };
+
+        let case_sensitive = match bench_matches.value_of("case_sensitive") {
+            Some(case) => case.parse()?,
+            None => false,
+        };
+
+        let mut options = AhoCorasickOptions::default();
+
+        if let Some(prefilter) = bench_matches.value_of("prefilter") {
+            options.prefilter = prefilter.parse()?;
+        }
+
+        if let Some(memory_limit) = bench_matches.value_of("memory_limit") {
+            options.memory_limit = Some(megabytes(memory_limit)?);
+        }
+
+        let mut corpus = vec![];
+
+        for input in matches.values_of("input").into_iter().flatten() {
+            for path in glob(input)? {
+                let mut document = String::new();
+                File::open(path?)?.read_to_string(&mut document)?;
+                corpus.push(document);
+            }
+        }
+
+        println!(
+            "{:<12} {:>12} {:>12} {:>12} {:>14} {:>10}",
+            "backend", "build", "memory", "throughput", "lines", "matches"
+        );
+
+        for result in bench::bench(&dictionary, &corpus, &backends, case_sensitive, &options)? {
+            println!("{}", result.pretty_display());
+        }
+
+        return Ok(());
+    }
+
     let mut tagger_builder = TaggerBuilder::default().dictionary(&dictionary);
 
     if let Some(matches) = matches.subcommand_matches("tagger") {
@@ -60,6 +194,59 @@ fn main() -> Result<(), Box<dyn Error>> {
             tagger_builder = tagger_builder.word_matching(word_matching.parse()?);
         }
 
+        if let Some(capitalization) = matches.value_of("capitalization") {
+            tagger_builder =
+                tagger_builder.capitalization(capitalization.parse::<Capitalization>()?);
+        }
+
+        if let Some(backend) = matches.value_of("backend") {
+            tagger_builder = tagger_builder.backend(backend.parse()?);
+        }
+
+        match matches.value_of("automaton_kind") {
+            Some("auto") => tagger_builder = tagger_builder.automaton_kind(None),
+            Some(kind) => tagger_builder = tagger_builder.backend(kind.parse()?),
+            None => {}
+        }
+
+        if let Some(prefilter) = matches.value_of("prefilter") {
+            tagger_builder = tagger_builder.prefilter(prefilter.parse()?);
+        }
+
+        if let Some(memory_limit) = matches.value_of("memory_limit") {
+            tagger_builder = tagger_builder.memory_limit(megabytes(memory_limit)?);
+        }
+
+        if let Some("tokens") = matches.value_of("engine") {
+            tagger_builder = tagger_builder.engine(Engine::Tokens);
+        }
+
+        if let Some(max_distance) = matches.value_of("fuzzy") {
+            tagger_builder = tagger_builder.fuzzy(max_distance.parse()?);
+        }
+
+        match matches.value_of("stemming") {
+            Some("french") => tagger_builder = tagger_builder.stemming(Algorithm::French),
+            Some("english") => tagger_builder = tagger_builder.stemming(Algorithm::English),
+            _ => {}
+        }
+
+        if let Some(class_options) = matches.values_of("class_options") {
+            for class_option in class_options {
+                match class_option.rsplit_once(':') {
+                    Some((class, options)) => {
+                        tagger_builder =
+                            tagger_builder.class_options(class, options.parse::<MatchOptions>()?);
+                    }
+                    None => return Err(Box::new(InvalidMatchOptions(class_option.to_string()))),
+                }
+            }
+        }
+
+        if let Some(max_segments) = matches.value_of("max_segments") {
+            tagger_builder = tagger_builder.max_segments(max_segments.parse()?);
+        }
+
         if let Some(match_kind) = matches.value_of("match_kind") {
             match match_kind {
                 "standard" => {
@@ -68,48 +255,396 @@ fn main() -> Result<(), Box<dyn Error>> {
                 "leftmostfirst" => {
                     tagger_builder = tagger_builder.match_kind(MatchKind::LeftmostFirst);
                 }
-                "leftmostlongest" | _ => {
+                _ => {
                     tagger_builder = tagger_builder.match_kind(MatchKind::LeftmostLongest);
                 }
             }
         }
     }
 
-    let tagger = tagger_builder.build()?;
+    let mut tagger = tagger_builder.build()?;
+
+    if let Some(matches) = matches.subcommand_matches("tagger") {
+        if let Some(paths) = matches.values_of("add") {
+            for path in paths {
+                let updates = DictionaryBuilder::from_file(path)?.build()?;
+                let added = tagger.add(&updates)?;
+                info!("{} entries added from {}", added, path);
+            }
+        }
+
+        if let Some(paths) = matches.values_of("remove") {
+            for path in paths {
+                for term in std::fs::read_to_string(path)?.lines() {
+                    if !tagger.remove(term) {
+                        warn!("Can't remove {:?}: unknown term", term);
+                    }
+                }
+            }
+        }
 
-    if let Some(mut input_files) = matches.values_of("input") {
-        while let Some(input) = input_files.next() {
+        if matches.is_present("compact") {
+            tagger.compact()?;
+        }
+    }
+
+    let mut html_report = HtmlReport::default();
+    let mut label_studio = LabelStudio(vec![]);
+    let label_map = LabelMap::new(&tagger.classes(), scheme);
+    let max_tokens = matches.value_of("max_tokens").unwrap_or("128").parse()?;
+    let mut records = 0;
+    let mut brat_names = HashSet::new();
+
+    if matches.value_of("format") == Some("huggingface") {
+        let output_dir = matches.value_of("output_dir").unwrap_or(".");
+        let path = PathBuf::from(output_dir).join("label2id.json");
+
+        std::fs::write(&path, label_map.to_string())?;
+        info!("Writing {}", path.display());
+    }
+
+    if matches.value_of("format") == Some("mentions") {
+        println!("{}", mentions_options.header());
+    }
+
+    if let Some(input_files) = matches.values_of("input") {
+        for input in input_files {
             for entry in glob(input).expect("Failed to read glob pattern") {
                 info!("Tagging {}", input.bold());
                 match entry {
                     Ok(path) => {
-                        let mut file = File::open(path)?;
+                        let mut file = File::open(&path)?;
                         let mut file_content = String::new();
 
                         file.read_to_string(&mut file_content)?;
 
-                        for line in file_content.lines() {
-                            let tagged_values = tagger.tag(&line);
+                        match matches.value_of("format") {
+                            Some("conll") => print!(
+                                "{}",
+                                Conll::new(sentences(
+                                    &tagger,
+                                    &file_content,
+                                    scheme,
+                                    tokenizer.as_ref()
+                                ))
+                                .with_options(conll_options.clone())
+                            ),
+                            Some("conllu") => {
+                                print!(
+                                    "{}",
+                                    ConllU::new(
+                                        path.to_string_lossy(),
+                                        sentences(
+                                            &tagger,
+                                            &file_content,
+                                            scheme,
+                                            tokenizer.as_ref()
+                                        )
+                                    )
+                                )
+                            }
+                            Some("json") => {
+                                let (tags, dropped) = tagger.tag_with_dropped(&file_content);
+                                let record = JsonRecord::new(
+                                    &file_content,
+                                    tags,
+                                    scheme,
+                                    tokenizer.as_ref(),
+                                    offsets,
+                                )
+                                .with_dropped(&dropped)
+                                .with_document(path.to_string_lossy());
+
+                                println!("{}", serde_json::to_string_pretty(&record)?);
+                            }
+                            Some("brat") => {
+                                let brat = Brat::from(tagger.tag_nested(&file_content));
+                                let output_dir = matches.value_of("output_dir").unwrap_or(".");
 
-                            match matches.value_of("format") {
-                                Some("bioes") | Some("BIOES") => {
-                                    println!("{}", BIOES::from(tagged_values).pretty_display());
+                                let written = brat.write(output_dir, &path, &mut brat_names)?;
+
+                                info!("Wrote {}", written.display());
+                            }
+                            Some("tei") if matches.is_present("tei_insert") => {
+                                println!("{}", tei_mapping.annotate(&file_content, &tagger)?);
+                            }
+                            Some("tei") => {
+                                let mut tei = Tei::new(path.to_string_lossy());
+
+                                for line in file_content.lines().filter(|l| !l.trim().is_empty()) {
+                                    tei.push(&tagger.tag(line), &tei_mapping);
                                 }
-                                Some("iob") | Some("IOB") | _ => {
-                                    println!("{}", IOB::from(tagged_values).pretty_display());
+
+                                println!("{}", tei);
+                            }
+                            Some("inline") => {
+                                for line in file_content.lines() {
+                                    println!("{}", inline_markup.render(&tagger.tag(line)));
                                 }
                             }
-                        }
+                            Some("html") => {
+                                html_report.document(path.to_string_lossy());
 
-                        println!("");
+                                for line in file_content.lines().filter(|l| !l.trim().is_empty()) {
+                                    html_report.push(&tagger.tag(line));
+                                }
+                            }
+                            Some("mentions") => {
+                                let mut mentions =
+                                    Mentions::new(path.to_string_lossy(), mentions_options.clone());
+
+                                for (index, line) in file_content.lines().enumerate() {
+                                    mentions.push(index + 1, line, &tagger.tag(line));
+                                }
+
+                                print!("{}", mentions);
+                            }
+                            Some("huggingface") => {
+                                for sentence in
+                                    sentences(&tagger, &file_content, scheme, tokenizer.as_ref())
+                                {
+                                    for record in HuggingFaceRecord::from_tags(
+                                        &sentence.tags,
+                                        &label_map,
+                                        max_tokens,
+                                    )? {
+                                        println!("{}", record.with_id(records));
+                                        records += 1;
+                                    }
+                                }
+                            }
+                            Some("doccano") => {
+                                for line in file_content.lines().filter(|l| !l.trim().is_empty()) {
+                                    let tags = tagger.tag(line);
+                                    println!("{}", Doccano::from_tags(line, &tags));
+                                }
+                            }
+                            Some("prodigy") => {
+                                for line in file_content.lines().filter(|l| !l.trim().is_empty()) {
+                                    let tags = tagger.tag(line);
+                                    println!("{}", Prodigy::from_tags(line, &tags));
+                                }
+                            }
+                            Some("label_studio") => {
+                                label_studio.0.extend(
+                                    file_content
+                                        .lines()
+                                        .filter(|line| !line.trim().is_empty())
+                                        .map(|line| {
+                                            LabelStudioTask::from_tags(line, &tagger.tag(line))
+                                        }),
+                                );
+                            }
+                            Some("jsonl") => {
+                                for line in file_content.lines() {
+                                    let (tags, dropped) = tagger.tag_with_dropped(line);
+                                    let record = JsonRecord::new(
+                                        line,
+                                        tags,
+                                        scheme,
+                                        tokenizer.as_ref(),
+                                        offsets,
+                                    )
+                                    .with_dropped(&dropped);
+
+                                    println!("{}", record);
+                                }
+                            }
+                            format => {
+                                for line in file_content.lines() {
+                                    match format {
+                                        Some("bioes") | Some("BIOES") => {
+                                            let tagged_values = tagger.tag(line);
+                                            println!(
+                                                "{}",
+                                                BIOES::from_tags(tagged_values, tokenizer.as_ref())
+                                                    .pretty_display()
+                                            );
+                                        }
+                                        Some(
+                                            name @ ("iob1" | "IOB1" | "ioe1" | "IOE1" | "ioe2"
+                                            | "IOE2" | "bilou" | "BILOU" | "io" | "IO"),
+                                        ) => {
+                                            let tagged_values = tagger.tag(line);
+                                            println!(
+                                                "{}",
+                                                SchemeTags::from_tags(
+                                                    tagged_values,
+                                                    name.parse::<Scheme>()?,
+                                                    tokenizer.as_ref()
+                                                )
+                                                .pretty_display()
+                                            );
+                                        }
+                                        Some("nested_bioes") | Some("NESTED_BIOES") => {
+                                            let tagged_values = tagger.tag_nested(line);
+                                            println!(
+                                                "{}",
+                                                NestedBIOES::from_tags(
+                                                    tagged_values,
+                                                    tokenizer.as_ref()
+                                                )
+                                                .pretty_display()
+                                            );
+                                        }
+                                        Some("standoff") => {
+                                            let tagged_values = tagger.tag_nested(line);
+                                            println!(
+                                                "{}",
+                                                Standoff::from(tagged_values)
+                                                    .with_offsets(offsets)
+                                                    .pretty_display()
+                                            );
+                                        }
+                                        _ => {
+                                            let tagged_values = tagger.tag(line);
+                                            println!(
+                                                "{}",
+                                                IOB::from_tags(tagged_values, tokenizer.as_ref())
+                                                    .pretty_display()
+                                            );
+                                        }
+                                    }
+                                }
+
+                                println!();
+                            }
+                        }
                     }
                     Err(e) => println!("{:?}", e),
                 }
             }
         }
+
+        if matches.value_of("format") == Some("html") {
+            println!("{}", html_report);
+        }
+
+        if matches.value_of("format") == Some("label_studio") {
+            println!("{}", label_studio);
+        }
     }
 
     info!("Done");
 
     Ok(())
 }
+
+/// Parses a size in megabytes into bytes
+fn megabytes(input: &str) -> Result<usize, Box<dyn Error>> {
+    Ok((input.parse::<f64>()? * 1_000_000.0) as usize)
+}
+
+/// Reads the annotations exported by a tool and prints them in a token format.
+/// Each annotated text is a sentence
+fn import(
+    matches: &ArgMatches,
+    tool: &str,
+    scheme: Scheme,
+    tokenizer: &dyn Tokenizer,
+    conll_options: &ConllOptions,
+    inline_markup: &InlineMarkup,
+) -> Result<(), Box<dyn Error>> {
+    for input in matches.values_of("input").into_iter().flatten() {
+        for path in glob(input)? {
+            let path = path?;
+            info!("Importing {}", path.display().to_string().bold());
+
+            let content = std::fs::read_to_string(&path)?;
+
+            if let Ok(from) = tool.parse::<Scheme>() {
+                let format = matches.value_of("format").unwrap_or_default();
+                let to = format
+                    .parse::<Scheme>()
+                    .map_err(|_| UnsupportedFormat(format.to_string()))?;
+
+                for tags in SchemeTags::read(&content, from)? {
+                    println!("{}", tags.convert(to).pretty_display());
+                    println!();
+                }
+
+                continue;
+            }
+
+            let texts = match tool {
+                "doccano" => Doccano::read(&content)?,
+                "label_studio" => LabelStudio::read(&content)?,
+                "inline" => content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| inline_markup.parse(line))
+                    .collect(),
+                _ => Prodigy::read(&content)?,
+            };
+
+            let sentences = || {
+                texts
+                    .iter()
+                    .map(|text| {
+                        let tags = SchemeTags::from_tags(text.tags(), scheme, tokenizer);
+                        Sentence::new(text.text.as_str(), tags)
+                    })
+                    .collect::<Vec<Sentence>>()
+            };
+
+            match matches.value_of("format") {
+                Some("conll") => print!(
+                    "{}",
+                    Conll::new(sentences()).with_options(conll_options.clone())
+                ),
+                Some("conllu") => print!("{}", ConllU::new(path.to_string_lossy(), sentences())),
+                Some(format @ ("iob" | "IOB")) | Some(format @ ("bioes" | "BIOES")) => {
+                    for text in &texts {
+                        match format {
+                            "bioes" | "BIOES" => {
+                                println!(
+                                    "{}",
+                                    BIOES::from_tags(text.tags(), tokenizer).pretty_display()
+                                )
+                            }
+                            _ => println!(
+                                "{}",
+                                IOB::from_tags(text.tags(), tokenizer).pretty_display()
+                            ),
+                        }
+                        println!();
+                    }
+                }
+                Some(format) => match format.parse::<Scheme>() {
+                    Ok(scheme) => {
+                        for text in &texts {
+                            println!(
+                                "{}",
+                                SchemeTags::from_tags(text.tags(), scheme, tokenizer)
+                                    .pretty_display()
+                            );
+                            println!();
+                        }
+                    }
+                    Err(_) => return Err(Box::new(UnsupportedFormat(format.to_string()))),
+                },
+                None => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Tags each line of a document as a sentence
+fn sentences<'a>(
+    tagger: &'a Tagger,
+    document: &'a str,
+    scheme: Scheme,
+    tokenizer: &dyn Tokenizer,
+) -> Vec<Sentence<'a>> {
+    document
+        .lines()
+        .map(|line| {
+            Sentence::new(
+                line,
+                SchemeTags::from_tags(tagger.tag(line), scheme, tokenizer),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,427 @@
+use crate::errors::{DoubleArrayBuildError, InvalidBackend, MemoryLimitExceeded};
+use crate::traits::{Find, HeapSize};
+use crate::types::*;
+use aho_corasick::{AhoCorasick, AhoCorasickKind, MatchKind};
+use daachorse::DoubleArrayAhoCorasick;
+use fst::raw::Output;
+use fst::Map;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Matches patterns in bytes. Results hold the index of the matched pattern
+pub trait Matcher: Find + HeapSize {}
+
+impl<T: Find + HeapSize> Matcher for T {}
+
+/// Data structures available to match dictionary terms in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Aho-Corasick automaton. The automaton kind is picked by aho-corasick when unset:
+    /// noncontiguous NFA, contiguous NFA (most compact) or DFA (fastest, highest memory)
+    AhoCorasick(Option<AhoCorasickKind>),
+    /// Double-array Aho-Corasick automaton (daachorse)
+    DoubleArray,
+    /// Finite state transducer walked from every character of the input
+    Fst,
+}
+
+/// Options of the Aho-Corasick backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AhoCorasickOptions {
+    /// Enables the prefilter searching rare bytes of the patterns before running the automaton
+    pub prefilter: bool,
+    /// Maximum heap memory of an automaton, in bytes. The automaton is rebuilt as a
+    /// contiguous NFA when it exceeds it
+    pub memory_limit: Option<usize>,
+}
+
+impl Default for AhoCorasickOptions {
+    fn default() -> Self {
+        AhoCorasickOptions {
+            prefilter: true,
+            memory_limit: None,
+        }
+    }
+}
+
+impl Backend {
+    pub const ALL: [Backend; 5] = [
+        Backend::AhoCorasick(Some(AhoCorasickKind::NoncontiguousNFA)),
+        Backend::AhoCorasick(Some(AhoCorasickKind::ContiguousNFA)),
+        Backend::AhoCorasick(Some(AhoCorasickKind::DFA)),
+        Backend::DoubleArray,
+        Backend::Fst,
+    ];
+
+    /// Builds a matcher reporting every (overlapping) occurrence of the given terms.
+    /// Case insensitive matchers are ASCII case insensitive
+    pub fn build(
+        &self,
+        terms: &[&str],
+        case_sensitive: bool,
+        options: &AhoCorasickOptions,
+    ) -> Result<Box<dyn Matcher>, Box<dyn Error>> {
+        match self {
+            Backend::AhoCorasick(kind) => Ok(Box::new(build_aho_corasick(
+                terms,
+                case_sensitive,
+                *kind,
+                options,
+            )?)),
+            Backend::DoubleArray => Ok(Box::new(DoubleArrayMatcher::new(terms, case_sensitive)?)),
+            Backend::Fst => Ok(Box::new(FstMatcher::new(terms, case_sensitive))),
+        }
+    }
+}
+
+/// Builds an Aho-Corasick automaton reporting overlapping matches. When the automaton exceeds the
+/// memory limit, it's rebuilt as a contiguous NFA, the most compact kind
+fn build_aho_corasick(
+    terms: &[&str],
+    case_sensitive: bool,
+    kind: Option<AhoCorasickKind>,
+    options: &AhoCorasickOptions,
+) -> Result<AhoCorasick, Box<dyn Error>> {
+    let mut kind = kind;
+
+    loop {
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .ascii_case_insensitive(!case_sensitive)
+            .prefilter(options.prefilter)
+            .kind(kind)
+            .build(terms)?;
+
+        let memory_usage = automaton.memory_usage();
+
+        match options.memory_limit {
+            Some(limit) if memory_usage > limit => {
+                let compact_kind = match automaton.kind() {
+                    AhoCorasickKind::DFA | AhoCorasickKind::NoncontiguousNFA => {
+                        AhoCorasickKind::ContiguousNFA
+                    }
+                    _ => {
+                        return Err(Box::new(MemoryLimitExceeded {
+                            limit,
+                            usage: memory_usage,
+                        }))
+                    }
+                };
+
+                warn!(
+                    "{:?} automaton uses {} bytes (limit {}), falling back to {:?}",
+                    automaton.kind(),
+                    memory_usage,
+                    limit,
+                    compact_kind
+                );
+
+                kind = Some(compact_kind);
+            }
+            _ => return Ok(automaton),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = InvalidBackend;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "aho-corasick" => Ok(Backend::AhoCorasick(None)),
+            "nfa" => Ok(Backend::AhoCorasick(Some(
+                AhoCorasickKind::NoncontiguousNFA,
+            ))),
+            "contiguous_nfa" => Ok(Backend::AhoCorasick(Some(AhoCorasickKind::ContiguousNFA))),
+            "dfa" => Ok(Backend::AhoCorasick(Some(AhoCorasickKind::DFA))),
+            "daachorse" => Ok(Backend::DoubleArray),
+            "fst" => Ok(Backend::Fst),
+            _ => Err(InvalidBackend(input.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::AhoCorasick(None) => write!(f, "aho-corasick"),
+            Backend::AhoCorasick(Some(AhoCorasickKind::NoncontiguousNFA)) => write!(f, "nfa"),
+            Backend::AhoCorasick(Some(AhoCorasickKind::ContiguousNFA)) => {
+                write!(f, "contiguous_nfa")
+            }
+            Backend::AhoCorasick(Some(AhoCorasickKind::DFA)) => write!(f, "dfa"),
+            Backend::AhoCorasick(Some(kind)) => write!(f, "{:?}", kind),
+            Backend::DoubleArray => write!(f, "daachorse"),
+            Backend::Fst => write!(f, "fst"),
+        }
+    }
+}
+
+/// Empty terms are not matched, as with the other backends
+impl Find for AhoCorasick {
+    fn find(&self, input: &str) -> Vec<FindResult> {
+        self.find_overlapping_iter(input)
+            .filter(|result| result.start() < result.end())
+            .map(|result| (result.start(), result.end(), result.pattern().as_usize()))
+            .collect()
+    }
+}
+
+impl HeapSize for AhoCorasick {
+    fn heap_bytes(&self) -> usize {
+        self.memory_usage()
+    }
+}
+
+/// Deduplicates terms (lowercased if the matcher is case insensitive).
+/// Returns the sorted unique patterns and the indexes of the terms each pattern stands for
+fn unique_patterns(terms: &[&str], case_sensitive: bool) -> (Vec<Vec<u8>>, Vec<Vec<usize>>) {
+    let mut patterns: BTreeMap<Vec<u8>, Vec<usize>> = BTreeMap::new();
+
+    for (index, term) in terms.iter().enumerate().filter(|(_, t)| !t.is_empty()) {
+        let pattern = match case_sensitive {
+            true => term.as_bytes().to_vec(),
+            false => term.as_bytes().to_ascii_lowercase(),
+        };
+
+        patterns.entry(pattern).or_default().push(index);
+    }
+
+    patterns.into_iter().unzip()
+}
+
+/// Lowercases the input of case insensitive matchers. ASCII lowercasing keeps byte positions
+fn normalize_input(input: &str, case_sensitive: bool) -> Vec<u8> {
+    match case_sensitive {
+        true => input.as_bytes().to_vec(),
+        false => input.as_bytes().to_ascii_lowercase(),
+    }
+}
+
+/// Double-array Aho-Corasick matcher
+pub struct DoubleArrayMatcher {
+    automaton: Option<DoubleArrayAhoCorasick<u32>>,
+    indexes: Vec<Vec<usize>>,
+    case_sensitive: bool,
+}
+
+impl DoubleArrayMatcher {
+    pub fn new(
+        terms: &[&str],
+        case_sensitive: bool,
+    ) -> Result<DoubleArrayMatcher, DoubleArrayBuildError> {
+        let (patterns, indexes) = unique_patterns(terms, case_sensitive);
+
+        // An empty set of patterns can't be compiled
+        let automaton = match patterns.is_empty() {
+            true => None,
+            false => Some(
+                DoubleArrayAhoCorasick::with_values(
+                    patterns
+                        .into_iter()
+                        .enumerate()
+                        .map(|(value, pattern)| (pattern, value as u32)),
+                )
+                .map_err(|error| DoubleArrayBuildError(error.to_string()))?,
+            ),
+        };
+
+        Ok(DoubleArrayMatcher {
+            automaton,
+            indexes,
+            case_sensitive,
+        })
+    }
+}
+
+impl Find for DoubleArrayMatcher {
+    fn find(&self, input: &str) -> Vec<FindResult> {
+        let automaton = match &self.automaton {
+            Some(automaton) => automaton,
+            None => return vec![],
+        };
+
+        automaton
+            .find_overlapping_iter(normalize_input(input, self.case_sensitive))
+            .flat_map(|result| {
+                self.indexes[result.value() as usize]
+                    .iter()
+                    .map(move |index| (result.start(), result.end(), *index))
+            })
+            .collect()
+    }
+}
+
+impl HeapSize for DoubleArrayMatcher {
+    fn heap_bytes(&self) -> usize {
+        let automaton_bytes = self.automaton.as_ref().map_or(0, |a| a.heap_bytes());
+
+        automaton_bytes + heap_bytes_of_indexes(&self.indexes)
+    }
+}
+
+/// Finite state transducer matcher. The transducer is walked from every character of the input
+pub struct FstMatcher {
+    map: Map<Vec<u8>>,
+    indexes: Vec<Vec<usize>>,
+    case_sensitive: bool,
+}
+
+impl FstMatcher {
+    pub fn new(terms: &[&str], case_sensitive: bool) -> FstMatcher {
+        let (patterns, indexes) = unique_patterns(terms, case_sensitive);
+
+        let map = Map::from_iter(
+            patterns
+                .into_iter()
+                .enumerate()
+                .map(|(value, pattern)| (pattern, value as u64)),
+        )
+        .expect("Patterns are sorted and unique");
+
+        FstMatcher {
+            map,
+            indexes,
+            case_sensitive,
+        }
+    }
+}
+
+impl Find for FstMatcher {
+    fn find(&self, input: &str) -> Vec<FindResult> {
+        let fst = self.map.as_fst();
+        let bytes = normalize_input(input, self.case_sensitive);
+        let mut results = vec![];
+
+        for start in (0..bytes.len()).filter(|start| input.is_char_boundary(*start)) {
+            let mut node = fst.root();
+            let mut output = Output::zero();
+
+            for (offset, byte) in bytes[start..].iter().enumerate() {
+                let transition = match node.find_input(*byte) {
+                    Some(index) => node.transition(index),
+                    None => break,
+                };
+
+                output = output.cat(transition.out);
+                node = fst.node(transition.addr);
+
+                if node.is_final() {
+                    let value = output.cat(node.final_output()).value() as usize;
+                    let end = start + offset + 1;
+
+                    results.extend(self.indexes[value].iter().map(|index| (start, end, *index)));
+                }
+            }
+        }
+
+        results
+    }
+}
+
+impl HeapSize for FstMatcher {
+    fn heap_bytes(&self) -> usize {
+        self.map.as_fst().as_bytes().len() + heap_bytes_of_indexes(&self.indexes)
+    }
+}
+
+fn heap_bytes_of_indexes(indexes: &[Vec<usize>]) -> usize {
+    indexes
+        .iter()
+        .map(|i| std::mem::size_of::<Vec<usize>>() + i.capacity() * std::mem::size_of::<usize>())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backends_find_the_same_matches() {
+        let terms = [
+            "Lyon",
+            "Lyon Part-Dieu",
+            "Part-Dieu",
+            "lyon",
+            "Lyon",
+            "Université de Lyon",
+            "é",
+            "",
+        ];
+        let input = "L'université de LYON, à Lyon Part-Dieu (près de Villeurbanne) : é É Lyonnais";
+
+        for case_sensitive in [true, false] {
+            let expected = Backend::AhoCorasick(None)
+                .build(&terms, case_sensitive, &AhoCorasickOptions::default())
+                .unwrap();
+            let mut expected = expected.find(input);
+            expected.sort();
+
+            assert!(!expected.is_empty());
+
+            for backend in Backend::ALL {
+                for prefilter in [true, false] {
+                    let options = AhoCorasickOptions {
+                        prefilter,
+                        memory_limit: None,
+                    };
+                    let matcher = backend.build(&terms, case_sensitive, &options).unwrap();
+                    let mut results = matcher.find(input);
+                    results.sort();
+
+                    assert_eq!(results, expected, "{} {}", backend, case_sensitive);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn automata_above_the_memory_limit_are_compacted() {
+        let terms = [
+            "Université de Lyon",
+            "Lyon Part-Dieu",
+            "Villeurbanne",
+            "Vénissieux",
+        ];
+        let options = |memory_limit| AhoCorasickOptions {
+            prefilter: true,
+            memory_limit,
+        };
+
+        let compact = build_aho_corasick(
+            &terms,
+            false,
+            Some(AhoCorasickKind::ContiguousNFA),
+            &options(None),
+        )
+        .unwrap();
+        let limit = compact.memory_usage();
+
+        let automaton = build_aho_corasick(
+            &terms,
+            false,
+            Some(AhoCorasickKind::DFA),
+            &options(Some(limit)),
+        )
+        .unwrap();
+        assert_eq!(automaton.kind(), AhoCorasickKind::ContiguousNFA);
+
+        let error = build_aho_corasick(
+            &terms,
+            false,
+            Some(AhoCorasickKind::DFA),
+            &options(Some(limit - 1)),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            MemoryLimitExceeded {
+                limit: limit - 1,
+                usage: limit
+            }
+            .to_string()
+        );
+    }
+}
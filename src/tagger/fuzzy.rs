@@ -0,0 +1,392 @@
+use crate::dict::Dictionary;
+use crate::tagger::words;
+use crate::types::*;
+use fst::{Automaton, IntoStreamer, Map, Streamer};
+use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Maximum number of edits tolerated by approximate matching
+pub const MAX_EDIT_DISTANCE: EditDistance = 2;
+
+/// Finds approximate matches of dictionary terms (e.g. OCR errors) using Levenshtein automata
+/// over an FST of the lowercased dictionary terms. Matches are aligned on words: the terms close
+/// to the pieces of text starting at a word are found with a single walk of the FST.
+pub struct FuzzyFinder {
+    terms: Map<Vec<u8>>,
+    /// Entries of each lowercased term, in dictionary order
+    indexes: Vec<Vec<DictionaryIndex>>,
+    automaton_builders: Vec<LevenshteinAutomatonBuilder>,
+    max_distance: EditDistance,
+    max_words: usize,
+    /// Number of words and length in characters of each lowercased term
+    shapes: BTreeSet<(usize, usize)>,
+    max_length: usize,
+}
+
+impl FuzzyFinder {
+    /// Builds a fuzzy finder tolerating at most `max_distance` edits per term (`MAX_EDIT_DISTANCE` at most)
+    pub fn new(dict: &Dictionary, max_distance: EditDistance) -> Result<FuzzyFinder, fst::Error> {
+        let max_distance = max_distance.min(MAX_EDIT_DISTANCE);
+        let mut terms: BTreeMap<String, Vec<DictionaryIndex>> = BTreeMap::new();
+
+        for (index, term) in dict.terms().into_iter().enumerate() {
+            terms.entry(term.to_lowercase()).or_default().push(index);
+        }
+
+        let shapes = terms
+            .keys()
+            .map(|term| (words(term).len(), term.chars().count()))
+            .collect::<BTreeSet<(usize, usize)>>();
+        let max_words = shapes.iter().map(|shape| shape.0).max().unwrap_or_default();
+        let max_length = shapes.iter().map(|shape| shape.1).max().unwrap_or_default();
+
+        let (terms, indexes): (Vec<String>, Vec<Vec<DictionaryIndex>>) = terms.into_iter().unzip();
+        let terms = Map::from_iter(
+            terms
+                .into_iter()
+                .enumerate()
+                .map(|(value, term)| (term, value as u64)),
+        )?;
+
+        let automaton_builders = (0..=max_distance)
+            .map(|distance| LevenshteinAutomatonBuilder::new(distance, true))
+            .collect();
+
+        Ok(FuzzyFinder {
+            terms,
+            indexes,
+            automaton_builders,
+            max_distance,
+            max_words,
+            shapes,
+            max_length,
+        })
+    }
+
+    /// Returns the edit distance allowed for a piece of text, depending on it's length in characters
+    fn allowed_distance(&self, length: usize) -> EditDistance {
+        match length {
+            0..=4 => 0,
+            5..=9 => self.max_distance.min(1),
+            _ => self.max_distance,
+        }
+    }
+
+    /// Returns true if a term may be within `distance` edits of a piece of text of the given
+    /// number of words and length. An edit changes the length and the number of words by one at most
+    fn may_match(&self, words: usize, length: usize, distance: EditDistance) -> bool {
+        let distance = distance as usize;
+
+        (words.saturating_sub(distance)..=words + distance).any(|words| {
+            self.shapes
+                .range((words, length.saturating_sub(distance))..=(words, length + distance))
+                .next()
+                .is_some()
+        })
+    }
+
+    /// Returns true if a piece of text is within the allowed edit distance of a term, case included
+    pub fn is_close(&self, text: &str, term: &str) -> bool {
+        let distance = self.allowed_distance(text.chars().count());
+        let dfa = self.automaton_builders[distance as usize].build_dfa(text);
+
+        matches!(dfa.eval(term), Distance::Exact(_))
+    }
+
+    /// Finds approximate matches in a text. Exact matches (distance 0) are not reported.
+    /// Only entries accepted for the matched text by `accepts` are matched, the first one
+    /// when several entries share a term
+    pub fn find<F: Fn(DictionaryIndex, &str) -> bool>(
+        &self,
+        input: &str,
+        accepts: F,
+    ) -> Vec<FuzzyFindResult> {
+        let words = words(input);
+        let mut results = vec![];
+
+        for (first, (start, _)) in words.iter().enumerate() {
+            let mut text: Vec<char> = vec![];
+            let mut windows: Vec<(usize, EditDistance, EndByte)> = vec![];
+            let mut text_end = *start;
+
+            // Only pieces of text shaped like a term are matched
+            for (count, (_, end)) in words.iter().skip(first).take(self.max_words).enumerate() {
+                text.extend(input[text_end..*end].to_lowercase().chars());
+                text_end = *end;
+
+                if text.len() > self.max_length + self.max_distance as usize {
+                    break;
+                }
+
+                let distance = self.allowed_distance(text.len());
+
+                if distance > 0 && self.may_match(count + 1, text.len(), distance) {
+                    windows.push((text.len(), distance, *end));
+                }
+            }
+
+            let last_length = match windows.last() {
+                Some((length, _, _)) => *length,
+                None => continue,
+            };
+            text.truncate(last_length);
+
+            // The terms close to every piece of text starting at this word are found at once
+            let automaton = WindowsAutomaton {
+                text: &text,
+                lengths: windows
+                    .iter()
+                    .map(|(length, distance, _)| (*length, *distance))
+                    .collect(),
+                max_distance: windows
+                    .iter()
+                    .map(|(_, distance, _)| *distance)
+                    .max()
+                    .unwrap_or_default(),
+            };
+            let mut best: Vec<Option<(EditDistance, DictionaryIndex)>> = vec![None; windows.len()];
+            let mut stream = self.terms.search_with_state(&automaton).into_stream();
+
+            while let Some((_, value, state)) = stream.next() {
+                let state = match state {
+                    Some(state) => state,
+                    None => continue,
+                };
+
+                for (window, (length, max_distance, end)) in windows.iter().enumerate() {
+                    let distance = state.distance(*length, max_distance + 1);
+
+                    if distance > *max_distance
+                        || best[window].is_some_and(|(best_distance, _)| best_distance <= distance)
+                    {
+                        continue;
+                    }
+
+                    let matched_text = &input[*start..*end];
+                    let accepted_index = self.indexes[value as usize]
+                        .iter()
+                        .find(|index| accepts(**index, matched_text));
+
+                    if let Some(index) = accepted_index {
+                        best[window] = Some((distance, *index));
+                    }
+                }
+            }
+
+            for ((_, _, end), best) in windows.iter().zip(best) {
+                if let Some((distance, index)) = best {
+                    if distance > 0 {
+                        results.push((*start, *end, index, distance));
+                    }
+                }
+            }
+        }
+
+        results
+    }
+}
+
+/// Number of cells of the band of edit distances kept by a `WindowsAutomaton`
+const BAND: usize = 2 * MAX_EDIT_DISTANCE as usize + 1;
+
+/// Edit distances between the term prefix read by a `WindowsAutomaton` and the prefixes of its
+/// text, capped above the maximum distance. Only the prefixes whose length differs from the length
+/// of the term prefix by `MAX_EDIT_DISTANCE` at most are kept, the others being farther
+#[derive(Debug, Clone, Copy)]
+struct WindowsState {
+    /// Number of characters of the term prefix
+    depth: usize,
+    band: [EditDistance; BAND],
+    previous_band: [EditDistance; BAND],
+    previous_char: Option<char>,
+    /// Bytes of the character being read
+    pending: ([u8; 4], usize),
+}
+
+impl WindowsState {
+    /// Returns the position in the band of the text prefix of the given length
+    fn cell(&self, length: usize) -> Option<usize> {
+        (length + MAX_EDIT_DISTANCE as usize)
+            .checked_sub(self.depth)
+            .filter(|cell| *cell < BAND)
+    }
+
+    /// Returns the edit distance between the term prefix and a text prefix, capped
+    fn distance(&self, length: usize, cap: EditDistance) -> EditDistance {
+        self.cell(length).map_or(cap, |cell| self.band[cell])
+    }
+}
+
+/// Levenshtein automaton (transpositions included) matching the terms close to any of the pieces
+/// of a text starting at its first character. Distances are computed one band per term character
+struct WindowsAutomaton<'a> {
+    text: &'a [char],
+    /// Length in characters and allowed distance of each piece of text
+    lengths: Vec<(usize, EditDistance)>,
+    max_distance: EditDistance,
+}
+
+impl Automaton for WindowsAutomaton<'_> {
+    type State = Option<WindowsState>;
+
+    fn start(&self) -> Self::State {
+        let cap = self.max_distance + 1;
+        let mut band = [cap; BAND];
+
+        for (cell, distance) in band.iter_mut().enumerate() {
+            if let Some(length) = cell.checked_sub(MAX_EDIT_DISTANCE as usize) {
+                if length <= self.text.len() {
+                    *distance = (length as EditDistance).min(cap);
+                }
+            }
+        }
+
+        Some(WindowsState {
+            depth: 0,
+            band,
+            previous_band: [cap; BAND],
+            previous_char: None,
+            pending: ([0; 4], 0),
+        })
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        let cap = self.max_distance + 1;
+
+        state.as_ref().is_some_and(|state| {
+            state.pending.1 == 0
+                && self
+                    .lengths
+                    .iter()
+                    .any(|(length, distance)| state.distance(*length, cap) <= *distance)
+        })
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.as_ref().is_some_and(|state| {
+            state
+                .band
+                .iter()
+                .any(|distance| *distance <= self.max_distance)
+        })
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        let mut state = (*state)?;
+        let (mut bytes, mut length) = state.pending;
+        bytes[length] = byte;
+        length += 1;
+
+        let c = match std::str::from_utf8(&bytes[..length]) {
+            Ok(c) => c.chars().next()?,
+            Err(error) if error.error_len().is_none() && length < 4 => {
+                state.pending = (bytes, length);
+                return Some(state);
+            }
+            Err(_) => return None,
+        };
+
+        let cap = self.max_distance + 1;
+        let depth = state.depth + 1;
+        let mut band = [cap; BAND];
+
+        // Cell `cell` of the band holds the text prefix of length `depth + cell - MAX_EDIT_DISTANCE`
+        for cell in 0..BAND {
+            let length = match (depth + cell).checked_sub(MAX_EDIT_DISTANCE as usize) {
+                Some(length) if length <= self.text.len() => length,
+                _ => continue,
+            };
+
+            // Deletion of the term character
+            let mut distance = state.band.get(cell + 1).map_or(cap, |d| d + 1);
+
+            if length > 0 {
+                let text_char = self.text[length - 1];
+
+                // Substitution (or match) and insertion of the text character
+                distance = distance
+                    .min(state.band[cell] + EditDistance::from(text_char != c))
+                    .min(
+                        cell.checked_sub(1)
+                            .map_or(cap, |previous| band[previous] + 1),
+                    );
+
+                // Transposition of two adjacent characters
+                if length > 1
+                    && state.previous_char == Some(text_char)
+                    && self.text[length - 2] == c
+                {
+                    distance = distance.min(state.previous_band[cell] + 1);
+                }
+            }
+
+            band[cell] = distance.min(cap);
+        }
+
+        Some(WindowsState {
+            depth,
+            band,
+            previous_band: state.band,
+            previous_char: Some(c),
+            pending: ([0; 4], 0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the edit distance computed by a windows automaton, capped above the maximum distance
+    fn windows_distance(text: &str, term: &str) -> EditDistance {
+        let text = text.chars().collect::<Vec<char>>();
+        let automaton = WindowsAutomaton {
+            text: &text,
+            lengths: vec![(text.len(), MAX_EDIT_DISTANCE)],
+            max_distance: MAX_EDIT_DISTANCE,
+        };
+
+        let state = term.bytes().fold(automaton.start(), |state, byte| {
+            automaton.accept(&state, byte)
+        });
+
+        state.map_or(MAX_EDIT_DISTANCE + 1, |state| {
+            state.distance(text.len(), MAX_EDIT_DISTANCE + 1)
+        })
+    }
+
+    #[test]
+    fn windows_automata_agree_with_levenshtein_automata() {
+        let builder = LevenshteinAutomatonBuilder::new(MAX_EDIT_DISTANCE, true);
+        let words = [
+            "lyon",
+            "lyom",
+            "loyn",
+            "ylon",
+            "lyonnais",
+            "lyo",
+            "",
+            "université",
+            "universtié",
+            "unïversité",
+            "𝔏yon",
+            "lyon 𝔏",
+            "part-dieu",
+            "partdieu",
+            "part dieux",
+        ];
+
+        for text in words {
+            let dfa = builder.build_dfa(text);
+
+            for term in words {
+                let expected = match dfa.eval(term) {
+                    Distance::Exact(distance) => distance,
+                    Distance::AtLeast(_) => MAX_EDIT_DISTANCE + 1,
+                };
+
+                assert_eq!(windows_distance(text, term), expected, "{} {}", text, term);
+            }
+        }
+    }
+}
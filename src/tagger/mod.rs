@@ -2,5 +2,17 @@
 pub mod tag;
 pub use tag::*;
 
+mod backend;
+mod fuzzy;
+mod offsets;
+mod stem;
 mod tagger;
+mod token;
+mod words;
+pub use backend::*;
+pub use fuzzy::*;
+pub use offsets::*;
+pub use stem::*;
 pub use tagger::*;
+pub use token::*;
+pub use words::*;
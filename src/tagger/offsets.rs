@@ -0,0 +1,220 @@
+use crate::errors::InvalidOffsets;
+use crate::types::*;
+use std::str::FromStr;
+
+/// Unit of the offsets reported by output formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffsetUnit {
+    /// UTF-8 bytes (offsets used internally)
+    Bytes,
+    /// Unicode scalar values (Python string indexes)
+    Chars,
+    /// UTF-16 code units (JavaScript string indexes)
+    Utf16,
+}
+
+/// Convention used to report the end of a span
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndConvention {
+    /// The end is the position of the last unit of the span
+    Inclusive,
+    /// The end is the position following the span
+    Exclusive,
+}
+
+/// Offsets reported by output formats. Defaults to inclusive byte offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Offsets {
+    pub unit: OffsetUnit,
+    pub end: EndConvention,
+}
+
+impl Default for Offsets {
+    fn default() -> Self {
+        Offsets {
+            unit: OffsetUnit::Bytes,
+            end: EndConvention::Inclusive,
+        }
+    }
+}
+
+impl FromStr for OffsetUnit {
+    type Err = InvalidOffsets;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "bytes" => Ok(OffsetUnit::Bytes),
+            "chars" => Ok(OffsetUnit::Chars),
+            "utf16" => Ok(OffsetUnit::Utf16),
+            _ => Err(InvalidOffsets(input.to_string())),
+        }
+    }
+}
+
+impl FromStr for EndConvention {
+    type Err = InvalidOffsets;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "inclusive" => Ok(EndConvention::Inclusive),
+            "exclusive" => Ok(EndConvention::Exclusive),
+            _ => Err(InvalidOffsets(input.to_string())),
+        }
+    }
+}
+
+/// Converts the byte offsets of a text into the selected unit and end convention
+#[derive(Debug, Clone)]
+pub struct OffsetConverter {
+    offsets: Offsets,
+    /// Position of every byte of the text (and of the end of the text) in the selected unit.
+    /// Empty for byte offsets
+    positions: Vec<usize>,
+}
+
+impl OffsetConverter {
+    pub fn new(text: &str, offsets: Offsets) -> OffsetConverter {
+        let mut positions = vec![];
+
+        if offsets.unit != OffsetUnit::Bytes {
+            positions.reserve(text.len() + 1);
+            let mut position = 0;
+
+            for c in text.chars() {
+                positions.extend(std::iter::repeat_n(position, c.len_utf8()));
+
+                position += match offsets.unit {
+                    OffsetUnit::Utf16 => c.len_utf16(),
+                    _ => 1,
+                };
+            }
+
+            positions.push(position);
+        }
+
+        OffsetConverter { offsets, positions }
+    }
+
+    /// Converts a byte position
+    pub fn position(&self, byte: usize) -> usize {
+        match self.offsets.unit {
+            OffsetUnit::Bytes => byte,
+            _ => self.positions[byte],
+        }
+    }
+
+    /// Converts a position back into a byte position. Returns None when the position doesn't
+    /// start a character or lies beyond the end of the text (byte positions are returned as is)
+    pub fn byte(&self, position: usize) -> Option<usize> {
+        match self.offsets.unit {
+            OffsetUnit::Bytes => Some(position),
+            _ => {
+                let byte = self.positions.partition_point(|p| *p < position);
+
+                match self.positions.get(byte) {
+                    Some(p) if *p == position => Some(byte),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Converts a span given by its first byte and its last byte (inclusive end)
+    pub fn span(&self, start: StartByte, end: EndByte) -> (usize, usize) {
+        let start_position = self.position(start);
+        let end_position = self.position(end + 1);
+
+        match self.offsets.end {
+            EndConvention::Inclusive => (start_position, end_position.saturating_sub(1)),
+            EndConvention::Exclusive => (start_position, end_position),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Né à 𝔏yon";
+
+    fn converter(unit: OffsetUnit, end: EndConvention) -> OffsetConverter {
+        OffsetConverter::new(TEXT, Offsets { unit, end })
+    }
+
+    #[test]
+    fn spans_are_converted() {
+        let lyon = (TEXT.find("𝔏yon").unwrap(), TEXT.len() - 1);
+        let letter = (lyon.0, lyon.0 + '𝔏'.len_utf8() - 1);
+        let accent = (1, 2);
+
+        let expected = [
+            (
+                OffsetUnit::Bytes,
+                EndConvention::Inclusive,
+                [(7, 13), (7, 10), (1, 2)],
+            ),
+            (
+                OffsetUnit::Bytes,
+                EndConvention::Exclusive,
+                [(7, 14), (7, 11), (1, 3)],
+            ),
+            (
+                OffsetUnit::Chars,
+                EndConvention::Inclusive,
+                [(5, 8), (5, 5), (1, 1)],
+            ),
+            (
+                OffsetUnit::Chars,
+                EndConvention::Exclusive,
+                [(5, 9), (5, 6), (1, 2)],
+            ),
+            (
+                OffsetUnit::Utf16,
+                EndConvention::Inclusive,
+                [(5, 9), (5, 6), (1, 1)],
+            ),
+            (
+                OffsetUnit::Utf16,
+                EndConvention::Exclusive,
+                [(5, 10), (5, 7), (1, 2)],
+            ),
+        ];
+
+        for (unit, end, spans) in expected {
+            let converter = converter(unit, end);
+            let converted = [lyon, letter, accent].map(|(start, end)| converter.span(start, end));
+
+            assert_eq!(converted, spans, "{:?} {:?}", unit, end);
+        }
+    }
+
+    #[test]
+    fn positions_are_converted_back_to_bytes() {
+        for unit in [OffsetUnit::Bytes, OffsetUnit::Chars, OffsetUnit::Utf16] {
+            let converter = converter(unit, EndConvention::Exclusive);
+            let boundaries = TEXT
+                .char_indices()
+                .map(|(byte, _)| byte)
+                .chain(std::iter::once(TEXT.len()));
+
+            for byte in boundaries {
+                assert_eq!(
+                    converter.byte(converter.position(byte)),
+                    Some(byte),
+                    "{:?}",
+                    unit
+                );
+            }
+        }
+
+        let chars = converter(OffsetUnit::Chars, EndConvention::Exclusive);
+        assert_eq!(chars.byte(10), None);
+
+        // The second code unit of a surrogate pair doesn't start a character
+        let utf16 = converter(OffsetUnit::Utf16, EndConvention::Exclusive);
+        assert_eq!(utf16.byte(5), Some(7));
+        assert_eq!(utf16.byte(6), None);
+        assert_eq!(utf16.byte(7), Some(11));
+        assert_eq!(utf16.byte(11), None);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::dict::Dictionary;
+use crate::tagger::words;
+use crate::traits::Find;
+use crate::types::*;
+use aho_corasick::{AhoCorasick, BuildError, MatchKind};
+use rust_stemmers::{Algorithm, Stemmer};
+
+/// Finds dictionary terms in their inflected forms. Dictionary terms and input words are
+/// reduced with a Snowball stemmer, matches are found on the stemmed words and projected
+/// back to the byte positions of the input.
+pub struct StemFinder {
+    stemmer: Stemmer,
+    automaton: AhoCorasick,
+    indexes: Vec<DictionaryIndex>,
+}
+
+impl StemFinder {
+    pub fn new(dict: &Dictionary, language: Algorithm) -> Result<StemFinder, BuildError> {
+        let stemmer = Stemmer::create(language);
+
+        let (indexes, terms): (Vec<DictionaryIndex>, Vec<String>) = dict
+            .terms()
+            .into_iter()
+            .enumerate()
+            .map(|(index, term)| (index, stem_words(&stemmer, term).0))
+            .filter(|(_, stemmed_term)| !stemmed_term.is_empty())
+            .unzip();
+
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(terms)?;
+
+        Ok(StemFinder {
+            stemmer,
+            automaton,
+            indexes,
+        })
+    }
+}
+
+/// Represents the position of a word in the stemmed text and in the input
+type StemmedWord = ((StartByte, EndByte), (StartByte, EndByte));
+
+/// Stems the words of a text and joins them with single spaces.
+/// Returns the stemmed text and, for each word, it's position in the stemmed text and in the input
+fn stem_words(stemmer: &Stemmer, input: &str) -> (String, Vec<StemmedWord>) {
+    let mut stemmed_text = String::new();
+    let mut positions = vec![];
+
+    for (start, end) in words(input) {
+        if !stemmed_text.is_empty() {
+            stemmed_text.push(' ');
+        }
+
+        let stemmed_start = stemmed_text.len();
+        stemmed_text.push_str(&stemmer.stem(&input[start..end].to_lowercase()));
+        positions.push(((stemmed_start, stemmed_text.len()), (start, end)));
+    }
+
+    (stemmed_text, positions)
+}
+
+/// Returns true if the words of a match keep the case of the words of a term.
+/// Characters past the end of the shorter word (inflected endings) are not compared
+pub fn keeps_case(term: &str, matched_text: &str) -> bool {
+    let term_words = words(term);
+    let matched_words = words(matched_text);
+
+    term_words.len() == matched_words.len()
+        && term_words.into_iter().zip(matched_words).all(
+            |((term_start, term_end), (start, end))| {
+                term[term_start..term_end]
+                    .chars()
+                    .zip(matched_text[start..end].chars())
+                    .all(|(term_char, c)| term_char.is_uppercase() == c.is_uppercase())
+            },
+        )
+}
+
+impl Find for StemFinder {
+    fn find(&self, input: &str) -> Vec<FindResult> {
+        let (stemmed_text, positions) = stem_words(&self.stemmer, input);
+
+        self.automaton
+            .find_overlapping_iter(&stemmed_text)
+            .filter_map(|result| {
+                // Only matches covering whole stemmed words are kept
+                let first = positions
+                    .binary_search_by_key(&result.start(), |(stemmed, _)| stemmed.0)
+                    .ok()?;
+                let last = positions
+                    .binary_search_by_key(&result.end(), |(stemmed, _)| stemmed.1)
+                    .ok()?;
+
+                Some((
+                    positions[first].1 .0,
+                    positions[last].1 .1,
+                    self.indexes[result.pattern().as_usize()],
+                ))
+            })
+            .collect::<Vec<FindResult>>()
+    }
+}
@@ -1,8 +1,11 @@
+use crate::traits::Tokenizer;
 use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
-#[derive(Debug, Clone)]
-/// Represents a set of tag
-pub struct Tags(pub Vec<Tag>);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Represents a set of tag. Tags borrow the tagged text and the class names of the dictionary
+pub struct Tags<'a>(pub Vec<Tag<'a>>);
 
 #[macro_export]
 macro_rules! tag {
@@ -15,65 +18,315 @@ macro_rules! tag {
     };
 }
 
-#[derive(Debug, Clone)]
-pub enum Tag {
-    Tagged(TaggedContent),
-    UnTagged(UntaggedContent),
+/// Returns a part of a text, borrowed when the text is borrowed
+pub fn sub_str<'a>(text: &Cow<'a, str>, start: usize, end: usize) -> Cow<'a, str> {
+    match text {
+        Cow::Borrowed(text) => Cow::Borrowed(&text[start..end]),
+        Cow::Owned(text) => Cow::Owned(text[start..end].to_string()),
+    }
+}
+
+impl<'a> Tags<'a> {
+    /// Builds a set of tags covering the whole text from sorted, non overlapping tagged spans.
+    /// Gaps between spans are filled with UntaggedContent
+    pub fn from_spans(text: &'a str, spans: &[TaggedContent<'a>]) -> Tags<'a> {
+        let mut tags: Vec<Tag> = vec![];
+        let mut last_end = 0;
+
+        for span in spans {
+            if span.start > last_end {
+                tags.push(tag![&text[last_end..span.start], [last_end => span.start - 1]]);
+            }
+
+            tags.push(span.clone().into());
+            last_end = span.end + 1;
+        }
+
+        // If no result is found in the input string the whole string is considered as UntaggedContent
+        if last_end < text.len() || tags.is_empty() {
+            tags.push(tag![&text[last_end..], [last_end => text.len().saturating_sub(1)]]);
+        }
+
+        Tags(tags)
+    }
+
+    /// Copies borrowed texts and classes, so that tags outlive the tagged text and the dictionary
+    #[allow(dead_code)]
+    pub fn into_owned(self) -> Tags<'static> {
+        Tags(self.0.into_iter().map(|tag| tag.into_owned()).collect())
+    }
 }
 
 #[derive(Debug, Clone)]
+/// Represents a set of possibly overlapping or nested tagged spans.
+/// Spans are arranged in layers: each layer holds non overlapping spans, outer spans come first.
+pub struct NestedTags<'a> {
+    pub text: Cow<'a, str>,
+    pub layers: Vec<Vec<TaggedContent<'a>>>,
+    /// Matches dropped because their capitalization doesn't fit the dictionary entry
+    pub dropped: Vec<TaggedContent<'a>>,
+}
+
+impl<'a> NestedTags<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(
+        text: S,
+        mut spans: Vec<TaggedContent<'a>>,
+    ) -> NestedTags<'a> {
+        // Longest spans first, so that a span is always placed above the spans it contains
+        spans.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+        let mut layers: Vec<Vec<TaggedContent>> = vec![];
+
+        for span in spans {
+            match layers
+                .iter_mut()
+                .find(|layer| layer.last().is_none_or(|last| last.end < span.start))
+            {
+                Some(layer) => layer.push(span),
+                None => layers.push(vec![span]),
+            }
+        }
+
+        NestedTags {
+            text: text.into(),
+            layers,
+            dropped: vec![],
+        }
+    }
+
+    /// Sets the matches dropped because of their capitalization
+    pub fn with_dropped(mut self, dropped: Vec<TaggedContent<'a>>) -> NestedTags<'a> {
+        self.dropped = dropped;
+        self
+    }
+
+    /// Returns all spans sorted by position
+    pub fn spans(&self) -> Vec<&TaggedContent<'a>> {
+        let mut spans = self
+            .layers
+            .iter()
+            .flatten()
+            .collect::<Vec<&TaggedContent>>();
+        spans.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+        spans
+    }
+
+    /// Returns the flat set of tags of a given layer
+    #[allow(dead_code)]
+    pub fn layer(&self, index: usize) -> Option<Tags<'_>> {
+        self.layers
+            .get(index)
+            .map(|spans| Tags::from_spans(&self.text, spans))
+    }
+
+    /// Copies borrowed texts and classes, so that tags outlive the tagged text and the dictionary
+    #[allow(dead_code)]
+    pub fn into_owned(self) -> NestedTags<'static> {
+        let into_owned = |spans: Vec<TaggedContent<'a>>| {
+            spans
+                .into_iter()
+                .map(|span| span.into_owned())
+                .collect::<Vec<TaggedContent<'static>>>()
+        };
+
+        NestedTags {
+            text: Cow::Owned(self.text.into_owned()),
+            layers: self.layers.into_iter().map(into_owned).collect(),
+            dropped: into_owned(self.dropped),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Tag<'a> {
+    Tagged(TaggedContent<'a>),
+    UnTagged(UntaggedContent<'a>),
+}
+
+impl Tag<'_> {
+    /// Copies borrowed texts and classes
+    pub fn into_owned(self) -> Tag<'static> {
+        match self {
+            Tag::Tagged(tagged_content) => Tag::Tagged(tagged_content.into_owned()),
+            Tag::UnTagged(untagged_content) => Tag::UnTagged(untagged_content.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a tagged piece of text
-pub struct TaggedContent {
-    pub original_text: Term,
+pub struct TaggedContent<'a> {
+    pub original_text: Cow<'a, str>,
     pub start: StartByte,
     pub end: EndByte,
-    pub class: String,
+    pub class: Cow<'a, str>,
+    /// Set when the text approximately matches a dictionary term
+    pub fuzzy: Option<FuzzyMatch<'a>>,
+    /// The dictionary entry the text matches
+    pub entry: Option<EntryMetadata<'a>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Represents the dictionary term an approximate match stands for
+pub struct FuzzyMatch<'a> {
+    pub term: Cow<'a, str>,
+    pub distance: EditDistance,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Represents the dictionary entry a tagged piece of text matches
+pub struct EntryMetadata<'a> {
+    pub term: Cow<'a, str>,
+    /// Identifier of the entity, when the dictionary gives one
+    pub identifier: Option<Cow<'a, str>>,
+    /// Path of the dictionary file
+    pub source: Cow<'a, str>,
+    pub priority: Priority,
 }
 
-impl<'a> TaggedContent {
-    pub fn new<S: Into<String>>(
+impl<'a> TaggedContent<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(
         original_text: S,
         start: StartByte,
         end: EndByte,
         class: S,
-    ) -> TaggedContent {
+    ) -> TaggedContent<'a> {
         TaggedContent {
             original_text: original_text.into(),
             start,
             end,
             class: class.into(),
+            fuzzy: None,
+            entry: None,
+        }
+    }
+
+    /// Marks the content as an approximate match of a dictionary term
+    pub fn with_fuzzy_match<S: Into<Cow<'a, str>>>(
+        mut self,
+        term: S,
+        distance: EditDistance,
+    ) -> TaggedContent<'a> {
+        self.fuzzy = Some(FuzzyMatch {
+            term: term.into(),
+            distance,
+        });
+        self
+    }
+
+    /// Sets the dictionary entry the content matches
+    pub fn with_entry<S: Into<Cow<'a, str>>>(
+        mut self,
+        term: S,
+        identifier: Option<S>,
+        source: S,
+        priority: Priority,
+    ) -> TaggedContent<'a> {
+        self.entry = Some(EntryMetadata {
+            term: term.into(),
+            identifier: identifier.map(|identifier| identifier.into()),
+            source: source.into(),
+            priority,
+        });
+        self
+    }
+
+    /// Splits the content into tokens. Tokens keep the class of the content and their position in the text
+    pub fn tokens(&self, tokenizer: &dyn Tokenizer) -> Vec<TaggedContent<'a>> {
+        tokenizer
+            .tokenize(&self.original_text)
+            .into_iter()
+            .map(|(start, end)| TaggedContent {
+                original_text: sub_str(&self.original_text, start, end),
+                start: self.start + start,
+                end: self.start + end - 1,
+                class: self.class.clone(),
+                fuzzy: self.fuzzy.clone(),
+                entry: self.entry.clone(),
+            })
+            .collect()
+    }
+
+    /// Copies borrowed texts and classes
+    pub fn into_owned(self) -> TaggedContent<'static> {
+        TaggedContent {
+            original_text: Cow::Owned(self.original_text.into_owned()),
+            start: self.start,
+            end: self.end,
+            class: Cow::Owned(self.class.into_owned()),
+            fuzzy: self.fuzzy.map(|fuzzy| FuzzyMatch {
+                term: Cow::Owned(fuzzy.term.into_owned()),
+                distance: fuzzy.distance,
+            }),
+            entry: self.entry.map(|entry| EntryMetadata {
+                term: Cow::Owned(entry.term.into_owned()),
+                identifier: entry
+                    .identifier
+                    .map(|identifier| Cow::Owned(identifier.into_owned())),
+                source: Cow::Owned(entry.source.into_owned()),
+                priority: entry.priority,
+            }),
         }
     }
 }
 
 /// Transforms a TaggedContent into a Tag
-impl<'a> From<TaggedContent> for Tag {
-    fn from(tagged_content: TaggedContent) -> Self {
+impl<'a> From<TaggedContent<'a>> for Tag<'a> {
+    fn from(tagged_content: TaggedContent<'a>) -> Self {
         Tag::Tagged(tagged_content)
     }
 }
 
 /// Transforms a UntaggedContent into a Tag
-impl<'a> From<UntaggedContent> for Tag {
-    fn from(tagged_content: UntaggedContent) -> Self {
+impl<'a> From<UntaggedContent<'a>> for Tag<'a> {
+    fn from(tagged_content: UntaggedContent<'a>) -> Self {
         Tag::UnTagged(tagged_content)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents an untagged piece of text
-pub struct UntaggedContent {
-    pub original_text: Term,
+pub struct UntaggedContent<'a> {
+    pub original_text: Cow<'a, str>,
     pub start: StartByte,
     pub end: EndByte,
 }
 
-impl<'a> UntaggedContent {
-    pub fn new<S: Into<Term>>(original_text: S, start: usize, end: usize) -> UntaggedContent {
+impl<'a> UntaggedContent<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(
+        original_text: S,
+        start: usize,
+        end: usize,
+    ) -> UntaggedContent<'a> {
         UntaggedContent {
             original_text: original_text.into(),
             start,
             end,
         }
     }
+
+    /// Splits the content into tokens. Tokens keep their position in the text
+    pub fn tokens(&self, tokenizer: &dyn Tokenizer) -> Vec<UntaggedContent<'a>> {
+        tokenizer
+            .tokenize(&self.original_text)
+            .into_iter()
+            .map(|(start, end)| {
+                UntaggedContent::new(
+                    sub_str(&self.original_text, start, end),
+                    self.start + start,
+                    self.start + end - 1,
+                )
+            })
+            .collect()
+    }
+
+    /// Copies the borrowed text
+    pub fn into_owned(self) -> UntaggedContent<'static> {
+        UntaggedContent {
+            original_text: Cow::Owned(self.original_text.into_owned()),
+            start: self.start,
+            end: self.end,
+        }
+    }
 }
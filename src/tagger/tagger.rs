@@ -1,15 +1,41 @@
-use crate::dict::Dictionary;
-use crate::errors::MissingDictionnary;
-use crate::tagger::{Tag, TaggedContent, Tags, UntaggedContent};
-use crate::traits::{Find, FindWord};
+use crate::dict::{Capitalization, Dictionary, DictionaryBuilder, MatchOptions};
+use crate::errors::{InvalidEditDistance, MissingDictionnary};
+use crate::tagger::{
+    keeps_case, AhoCorasickOptions, Backend, FuzzyFinder, Matcher, NestedTags, StemFinder,
+    TaggedContent, Tags, TokenFinder, MAX_EDIT_DISTANCE,
+};
+use crate::traits::{Find, FindWord, HeapSize};
 use crate::types::*;
-use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use aho_corasick::{AhoCorasickKind, MatchKind};
+use log::Level;
+use rust_stemmers::Algorithm;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
 
 pub struct TaggerBuilder<'a> {
     dict: Option<&'a Dictionary>,
     case_sensitive: bool,
     match_kind: MatchKind,
     word_matching: bool,
+    capitalization: Capitalization,
+    class_options: HashMap<Class, MatchOptions>,
+    fuzzy: EditDistance,
+    stemming: Option<Algorithm>,
+    engine: Engine,
+    backend: Backend,
+    aho_corasick_options: AhoCorasickOptions,
+    max_segments: usize,
+}
+
+/// The engine used to match dictionary terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// Matches raw bytes, word boundaries are checked afterwards
+    Bytes,
+    /// Matches sequences of token ids, matches are aligned on tokens by construction
+    Tokens,
 }
 
 /// Create a new tagger with a given dictionnary
@@ -34,90 +60,1005 @@ impl<'a> TaggerBuilder<'a> {
         self
     }
 
-    pub fn build(self) -> Result<Tagger<'a>, MissingDictionnary> {
-        match self.dict {
-            Some(dict) => {
-                info!("Building Tagger (AhoCorasick FSA)");
+    /// Sets the default capitalization constraint
+    pub fn capitalization(mut self, capitalization: Capitalization) -> TaggerBuilder<'a> {
+        self.capitalization = capitalization;
+        self
+    }
 
-                let ac_fsa = AhoCorasickBuilder::new()
-                    .match_kind(self.match_kind)
-                    .ascii_case_insensitive(!self.case_sensitive)
-                    .build(dict.terms());
+    /// Sets the engine used to match dictionary terms
+    pub fn engine(mut self, engine: Engine) -> TaggerBuilder<'a> {
+        self.engine = engine;
+        self
+    }
 
-                info!("Tagger builded");
+    /// Sets the data structure used by the bytes engine to match dictionary terms
+    pub fn backend(mut self, backend: Backend) -> TaggerBuilder<'a> {
+        self.backend = backend;
+        self
+    }
 
-                Ok(Tagger {
-                    dict: dict,
-                    finder: ac_fsa,
-                })
-            }
-            None => Err(MissingDictionnary),
+    /// Sets the kind of Aho-Corasick automaton (picked automatically when unset)
+    pub fn automaton_kind(mut self, kind: Option<AhoCorasickKind>) -> TaggerBuilder<'a> {
+        self.backend = Backend::AhoCorasick(kind);
+        self
+    }
+
+    /// Enables/Disables the prefilter of Aho-Corasick automata
+    pub fn prefilter(mut self, prefilter: bool) -> TaggerBuilder<'a> {
+        self.aho_corasick_options.prefilter = prefilter;
+        self
+    }
+
+    /// Sets the maximum heap memory of each Aho-Corasick automaton, in bytes.
+    /// Automata exceeding it are rebuilt as contiguous NFAs, the most compact kind
+    pub fn memory_limit(mut self, memory_limit: usize) -> TaggerBuilder<'a> {
+        self.aho_corasick_options.memory_limit = Some(memory_limit);
+        self
+    }
+
+    /// Enables approximate matching with at most `max_distance` edits per term (0 disables it,
+    /// `MAX_EDIT_DISTANCE` at most). Exact matches take priority over approximate ones
+    pub fn fuzzy(mut self, max_distance: EditDistance) -> TaggerBuilder<'a> {
+        self.fuzzy = max_distance;
+        self
+    }
+
+    /// Enables matching of inflected forms: dictionary terms and input words are reduced
+    /// with a Snowball stemmer of the given language before matching
+    pub fn stemming(mut self, language: Algorithm) -> TaggerBuilder<'a> {
+        self.stemming = Some(language);
+        self
+    }
+
+    /// Sets matching options for every entry of a class.
+    /// Class options override dictionary file options and are overridden by entry options
+    pub fn class_options<S: Into<Class>>(
+        mut self,
+        class: S,
+        options: MatchOptions,
+    ) -> TaggerBuilder<'a> {
+        self.class_options.insert(class.into(), options);
+        self
+    }
+
+    /// Resolves the matching options of every dictionary entry
+    fn resolve_options(&self, dict: &Dictionary) -> Vec<MatchOptions> {
+        let defaults = MatchOptions {
+            case_sensitive: Some(self.case_sensitive),
+            word_matching: Some(self.word_matching),
+            capitalization: Some(self.capitalization),
+        };
+
+        dict.entries()
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let class_options = self
+                    .class_options
+                    .get(dict.get_class(index))
+                    .cloned()
+                    .unwrap_or_default();
+
+                entry
+                    .options()
+                    .or(class_options)
+                    .or(dict.get_source(entry).options())
+                    .or(defaults)
+            })
+            .collect()
+    }
+
+    /// Sets the number of segments above which the tagger is compacted. Entries added to a tagger
+    /// are matched by small delta automata until they're merged with the base automata
+    pub fn max_segments(mut self, max_segments: usize) -> TaggerBuilder<'a> {
+        self.max_segments = max_segments.max(1);
+        self
+    }
+
+    pub fn build(self) -> Result<Tagger<'a>, Box<dyn Error>> {
+        let dict = match self.dict {
+            Some(dict) => dict,
+            None => return Err(Box::new(MissingDictionnary)),
+        };
+
+        if self.fuzzy > MAX_EDIT_DISTANCE {
+            return Err(Box::new(InvalidEditDistance {
+                distance: self.fuzzy,
+                max: MAX_EDIT_DISTANCE,
+            }));
+        }
+
+        info!(
+            "Building Tagger ({:?} engine, {} backend)",
+            self.engine, self.backend
+        );
+
+        let base = self.build_segment(Cow::Borrowed(dict), 0)?;
+
+        info!(
+            "Tagger builded ({} automata, {:.2} MB)",
+            base.finders.len(),
+            base.heap_bytes() as f64 / 1_000_000.0
+        );
+
+        Ok(Tagger {
+            segments: vec![base],
+            tombstones: HashSet::new(),
+            config: self,
+        })
+    }
+
+    /// Builds the automata matching the entries of a dictionary
+    fn build_segment(
+        &self,
+        dict: Cow<'a, Dictionary>,
+        offset: DictionaryIndex,
+    ) -> Result<Segment<'a>, Box<dyn Error>> {
+        // Entries sharing the same options are matched by the same automaton
+        let mut groups: HashMap<MatchOptions, Vec<DictionaryIndex>> = HashMap::new();
+        let options = self.resolve_options(&dict);
+
+        for (index, options) in options.iter().enumerate() {
+            groups.entry(*options).or_default().push(index);
+        }
+
+        let mut finders = vec![];
+
+        for (options, indexes) in groups {
+            let terms = indexes
+                .iter()
+                .map(|index| dict.get_term(*index))
+                .collect::<Terms>();
+
+            let case_sensitive = options.case_sensitive.unwrap_or_default();
+
+            // Overlapping matches are collected from every automaton and selected afterwards
+            let matcher: Box<dyn Matcher> = match self.engine {
+                Engine::Bytes => {
+                    self.backend
+                        .build(&terms, case_sensitive, &self.aho_corasick_options)?
+                }
+                Engine::Tokens => Box::new(TokenFinder::new(&terms, case_sensitive)?),
+            };
+
+            finders.push(Finder {
+                matcher,
+                token_aligned: self.engine == Engine::Tokens,
+                indexes,
+                word_matching: options.word_matching.unwrap_or_default(),
+                capitalization: options.capitalization.unwrap_or(Capitalization::Any),
+            });
         }
+
+        finders.sort_by_key(|finder| finder.indexes.first().cloned());
+
+        let fuzzy_finder = match self.fuzzy {
+            0 => None,
+            max_distance => {
+                info!("Building fuzzy finder (Levenshtein automata over FST)");
+                Some(FuzzyFinder::new(&dict, max_distance)?)
+            }
+        };
+
+        let stem_finder = match self.stemming {
+            Some(language) => {
+                info!("Building stemmed terms automaton ({:?})", language);
+                Some(StemFinder::new(&dict, language)?)
+            }
+            None => None,
+        };
+
+        Ok(Segment {
+            dict,
+            offset,
+            options,
+            finders,
+            fuzzy_finder,
+            stem_finder,
+        })
     }
 }
 
-impl<'a> Default for TaggerBuilder<'a> {
+impl Default for TaggerBuilder<'_> {
     fn default() -> Self {
         TaggerBuilder {
             dict: None,
             case_sensitive: false,
             match_kind: MatchKind::LeftmostLongest,
             word_matching: true,
+            capitalization: Capitalization::Any,
+            class_options: HashMap::new(),
+            fuzzy: 0,
+            stemming: None,
+            engine: Engine::Bytes,
+            backend: Backend::AhoCorasick(None),
+            aho_corasick_options: AhoCorasickOptions::default(),
+            max_segments: 8,
         }
     }
 }
 
-/// A struct used to tag text with a dictionary
+/// An automaton matching the dictionary entries sharing the same matching options
+struct Finder {
+    matcher: Box<dyn Matcher>,
+    token_aligned: bool,
+    indexes: Vec<DictionaryIndex>,
+    word_matching: bool,
+    capitalization: Capitalization,
+}
+
+impl Finder {
+    /// Returns true if matches need to be checked against word boundaries
+    fn checks_word_boundaries(&self) -> bool {
+        self.word_matching && !self.token_aligned
+    }
+}
+
+impl Find for Finder {
+    fn find(&self, input: &str) -> Vec<FindResult> {
+        self.matcher
+            .find(input)
+            .into_iter()
+            .map(|(start, end, pattern)| (start, end, self.indexes[pattern]))
+            .collect()
+    }
+}
+
+impl FindWord for Finder {}
+
+/// A dictionary and the automata matching its entries.
+/// Entries of a segment are numbered from the offset of the segment
+struct Segment<'a> {
+    dict: Cow<'a, Dictionary>,
+    offset: DictionaryIndex,
+    /// Resolved matching options of each entry
+    options: Vec<MatchOptions>,
+    finders: Vec<Finder>,
+    fuzzy_finder: Option<FuzzyFinder>,
+    stem_finder: Option<StemFinder>,
+}
+
+impl Segment<'_> {
+    /// Returns the offset of the next segment
+    fn end(&self) -> DictionaryIndex {
+        self.offset + self.dict.len()
+    }
+}
+
+impl HeapSize for Segment<'_> {
+    fn heap_bytes(&self) -> usize {
+        self.finders
+            .iter()
+            .map(|finder| finder.matcher.heap_bytes())
+            .sum::<usize>()
+    }
+}
+
+/// A struct used to tag text with a dictionary.
+///
+/// Entries can be added and removed once the tagger is built: added entries are matched
+/// by small delta segments and removed entries are hidden by tombstones, until the segments
+/// are compacted into a single one. Tagging results are the same as the results of a tagger
+/// built from scratch with the updated dictionary, added entries coming last.
 pub struct Tagger<'a> {
-    dict: &'a Dictionary,
-    finder: AhoCorasick,
+    segments: Vec<Segment<'a>>,
+    tombstones: HashSet<DictionaryIndex>,
+    config: TaggerBuilder<'a>,
 }
 
 impl<'a> Tagger<'a> {
-    /// Peform text annotation on a given text
-    pub fn tag(&self, text: &'a str) -> Tags {
-        let results = self.find_word(text);
-        let mut peekable_result = results.iter().peekable();
+    /// Peform text annotation on a given text.
+    /// Overlapping matches are resolved according to the match kind (leftmost longest for the standard match kind)
+    pub fn tag<'t>(&'t self, text: &'t str) -> Tags<'t> {
+        self.tag_with_dropped(text).0
+    }
+
+    /// Peform text annotation on a given text. Also returns the matches dropped because
+    /// of their capitalization
+    pub fn tag_with_dropped<'t>(&'t self, text: &'t str) -> (Tags<'t>, Vec<TaggedContent<'t>>) {
+        let (results, dropped) = self.candidates(text);
+        let dropped = self.spans(text, dropped);
 
-        let mut tags: Vec<Tag> = vec![];
+        if log_enabled!(Level::Debug) {
+            for span in &dropped {
+                debug!(
+                    "Dropped {:?} ({}): capitalization mismatch",
+                    span.original_text, span.class
+                );
+            }
+        }
+
+        let results = self.select(results);
+        let spans = self.with_fuzzy_spans(text, self.spans(text, results));
+
+        (Tags::from_spans(text, &spans), dropped)
+    }
+
+    /// Peform text annotation on a given text, keeping overlapping and nested matches
+    pub fn tag_nested<'t>(&'t self, text: &'t str) -> NestedTags<'t> {
+        let (results, dropped) = self.candidates(text);
 
-        let mut lock = false;
+        let results = match self.config.match_kind {
+            MatchKind::Standard => results,
+            _ => self.select(results),
+        };
 
-        while let Some((start, end, category)) = peekable_result.next() {
-            if *start > 0 && lock == false {
-                tags.push(tag!(&text[..*start], [0 => *start - 1]).into())
+        let spans = self.with_fuzzy_spans(text, self.spans(text, results));
+
+        NestedTags::new(text, spans).with_dropped(self.spans(text, dropped))
+    }
+
+    /// Adds the entries of a dictionary. An entry whose term is already known replaces the known
+    /// entry if it has a higher priority and is ignored otherwise, as when dictionaries are loaded together.
+    /// Returns the number of added entries
+    pub fn add(&mut self, dict: &Dictionary) -> Result<usize, Box<dyn Error>> {
+        let terms = dict.terms().into_iter().collect::<HashSet<&str>>();
+        let known = self.live_entries(&terms);
+
+        let mut added = vec![];
+        let mut replaced = vec![];
+
+        for (index, term) in dict.terms().into_iter().enumerate() {
+            match known.get(term) {
+                Some(known) if self.get_priority(*known) >= dict.get_priority(index) => {}
+                Some(known) => {
+                    replaced.push(*known);
+                    added.push(index);
+                }
+                None => added.push(index),
             }
+        }
 
-            lock = true;
+        if added.is_empty() {
+            return Ok(0);
+        }
 
-            tags.push(
-                tag![&text[*start..*end], [*start => *end - 1], &self.dict.get_class(*category)],
-            );
+        let last_rank = self
+            .segments
+            .iter()
+            .flat_map(|segment| segment.dict.sources())
+            .map(|source| source.rank())
+            .max()
+            .unwrap_or_default();
+
+        let mut builder = DictionaryBuilder::default();
+        builder.extend_from(dict, &added)?;
+        builder.rank_after(last_rank)?;
 
-            if let Some((next_start, _, _)) = peekable_result.peek() {
-                tags.push(tag![&text[*end..*next_start], [*end => *next_start - 1]]);
+        let offset = self.segments.last().map_or(0, |segment| segment.end());
+        let segment = self
+            .config
+            .build_segment(Cow::Owned(builder.build()?), offset)?;
+
+        debug!(
+            "Segment of {} entries added ({:.2} MB)",
+            added.len(),
+            segment.heap_bytes() as f64 / 1_000_000.0
+        );
+
+        self.segments.push(segment);
+        self.tombstones.extend(replaced);
+
+        if self.segments.len() > self.config.max_segments {
+            self.compact()?;
+        }
+
+        Ok(added.len())
+    }
+
+    /// Removes the entry of a term. Returns false if the term is unknown
+    pub fn remove(&mut self, term: &str) -> bool {
+        let terms = [term].iter().copied().collect::<HashSet<&str>>();
+
+        match self.live_entries(&terms).get(term) {
+            Some(index) => self.tombstones.insert(*index),
+            None => false,
+        }
+    }
+
+    /// Returns the uniq classes of every segment, sorted
+    pub fn classes(&self) -> Classes<'_> {
+        let mut classes = self
+            .segments
+            .iter()
+            .flat_map(|segment| segment.dict.classes())
+            .collect::<Classes>();
+
+        classes.sort();
+        classes.dedup();
+
+        classes
+    }
+
+    /// Merges every segment into a single one, dropping removed entries
+    pub fn compact(&mut self) -> Result<(), Box<dyn Error>> {
+        info!(
+            "Compacting {} segments ({} removed entries)",
+            self.segments.len(),
+            self.tombstones.len()
+        );
+
+        let mut builder = DictionaryBuilder::default();
+
+        for segment in &self.segments {
+            let live = (0..segment.dict.len())
+                .filter(|index| self.is_live(segment.offset + index))
+                .collect::<Vec<DictionaryIndex>>();
+
+            builder.extend_from(&segment.dict, &live)?;
+        }
+
+        let base = self.config.build_segment(Cow::Owned(builder.build()?), 0)?;
+
+        info!(
+            "Tagger compacted ({} automata, {:.2} MB)",
+            base.finders.len(),
+            base.heap_bytes() as f64 / 1_000_000.0
+        );
+
+        self.segments = vec![base];
+        self.tombstones.clear();
+
+        Ok(())
+    }
+
+    /// Returns the live entries of the given terms
+    fn live_entries<'q>(&self, terms: &HashSet<&'q str>) -> HashMap<&'q str, DictionaryIndex> {
+        let mut entries = HashMap::new();
+
+        for segment in &self.segments {
+            for (index, term) in segment.dict.terms().into_iter().enumerate() {
+                if let Some(term) = terms.get(term) {
+                    if self.is_live(segment.offset + index) {
+                        entries.insert(*term, segment.offset + index);
+                    }
+                }
             }
         }
 
-        // If no result is found in the input string the whole string is considered as UntaggedContent
-        if lock == false {
-            tags.push(tag![text, [0 => text.len()]]);
+        entries
+    }
+
+    fn is_live(&self, index: DictionaryIndex) -> bool {
+        !self.tombstones.contains(&index)
+    }
+
+    /// Returns the segment of an entry and the index of the entry in the segment dictionary
+    fn segment(&self, index: DictionaryIndex) -> (&Segment<'a>, DictionaryIndex) {
+        let position = self
+            .segments
+            .partition_point(|segment| segment.offset <= index)
+            - 1;
+        let segment = &self.segments[position];
+
+        (segment, index - segment.offset)
+    }
+
+    fn get_term(&self, index: DictionaryIndex) -> &str {
+        let (segment, index) = self.segment(index);
+        segment.dict.get_term(index)
+    }
+
+    fn get_class(&self, index: DictionaryIndex) -> &str {
+        let (segment, index) = self.segment(index);
+        segment.dict.get_class(index)
+    }
+
+    fn get_identifier(&self, index: DictionaryIndex) -> Option<&str> {
+        let (segment, index) = self.segment(index);
+        segment.dict.get_identifier(index)
+    }
+
+    fn get_source(&self, index: DictionaryIndex) -> Cow<'_, str> {
+        let (segment, index) = self.segment(index);
+        let entry = &segment.dict.entries()[index];
+        segment.dict.get_source(entry).path().to_string_lossy()
+    }
+
+    fn get_priority(&self, index: DictionaryIndex) -> Priority {
+        let (segment, index) = self.segment(index);
+        segment.dict.get_priority(index)
+    }
+
+    fn get_rank(&self, index: DictionaryIndex) -> SourceIndex {
+        let (segment, index) = self.segment(index);
+        segment.dict.get_rank(index)
+    }
+
+    /// Finds matches of every automaton, each one applying its own word matching option.
+    /// Returns kept matches and matches dropped because of their capitalization
+    fn candidates(&self, text: &str) -> (Vec<FindResult>, Vec<FindResult>) {
+        let mut kept = vec![];
+        let mut dropped = vec![];
+
+        for segment in &self.segments {
+            for finder in &segment.finders {
+                let results = if finder.checks_word_boundaries() {
+                    finder.find_word(text)
+                } else {
+                    finder.find(text)
+                };
+
+                for (start, end, index) in results {
+                    let result = (start, end, segment.offset + index);
+
+                    if !self.is_live(result.2) {
+                        continue;
+                    }
+
+                    let term = segment.dict.get_term(index);
+
+                    if finder.capitalization.accepts(term, &text[start..end], text) {
+                        kept.push(result);
+                    } else {
+                        dropped.push(result);
+                    }
+                }
+            }
+
+            // Stemmed matches are word aligned by construction
+            if let Some(stem_finder) = &segment.stem_finder {
+                for (start, end, index) in stem_finder.find(text) {
+                    let result = (start, end, segment.offset + index);
+                    let options = segment.options[index];
+                    let term = segment.dict.get_term(index);
+                    let matched_text = &text[start..end];
+
+                    if !self.is_live(result.2)
+                        || options.case_sensitive.unwrap_or_default()
+                            && !keeps_case(term, matched_text)
+                    {
+                        continue;
+                    }
+
+                    let capitalization = options.capitalization.unwrap_or(Capitalization::Any);
+
+                    if capitalization.accepts(term, matched_text, text) {
+                        kept.push(result);
+                    } else {
+                        dropped.push(result);
+                    }
+                }
+            }
         }
 
-        Tags(tags)
+        // A span kept by another automaton isn't dropped
+        let kept_spans = kept
+            .iter()
+            .map(|(start, end, _)| (*start, *end))
+            .collect::<HashSet<(StartByte, EndByte)>>();
+        dropped.retain(|(start, end, _)| !kept_spans.contains(&(*start, *end)));
+
+        (kept, dropped)
+    }
+
+    /// Selects non overlapping results. Results of higher priority dictionaries are selected first,
+    /// then results of earlier dictionary files (on equal priorities), then results starting
+    /// at the same position are ranked by length (leftmost longest) or by dictionary order (leftmost first)
+    fn select(&self, mut results: Vec<FindResult>) -> Vec<FindResult> {
+        let precedence = |result: &FindResult| {
+            (
+                Reverse(self.get_priority(result.2)),
+                self.get_rank(result.2),
+            )
+        };
+
+        match self.config.match_kind {
+            MatchKind::LeftmostFirst => results.sort_by(|a, b| {
+                precedence(a)
+                    .cmp(&precedence(b))
+                    .then(a.0.cmp(&b.0))
+                    .then(a.2.cmp(&b.2))
+            }),
+            _ => results.sort_by(|a, b| {
+                precedence(a)
+                    .cmp(&precedence(b))
+                    .then(a.0.cmp(&b.0))
+                    .then(b.1.cmp(&a.1))
+                    .then(a.2.cmp(&b.2))
+            }),
+        }
+
+        // Selected results indexed by start position
+        let mut selected: BTreeMap<StartByte, FindResult> = BTreeMap::new();
+
+        for result in results {
+            let (start, end, _) = result;
+
+            let overlaps_previous = selected
+                .range(..=start)
+                .next_back()
+                .is_some_and(|(_, previous)| previous.1 > start);
+            let overlaps_next = selected.range(start..end).next().is_some();
+
+            if !overlaps_previous && !overlaps_next {
+                selected.insert(start, result);
+            }
+        }
+
+        selected.into_values().collect()
+    }
+
+    /// Finds approximate matches of every segment. When several segments match the same
+    /// piece of text, the match a single fuzzy finder would report is kept: the closest term,
+    /// then the first one in lowercase order, then the first entry
+    fn fuzzy_candidates(&self, text: &str) -> Vec<FuzzyFindResult> {
+        let mut best: BTreeMap<(StartByte, EndByte), FuzzyFindResult> = BTreeMap::new();
+
+        for segment in &self.segments {
+            if let Some(fuzzy_finder) = &segment.fuzzy_finder {
+                // Case sensitive entries must be close to the matched text without lowercasing
+                let results = fuzzy_finder.find(text, |index, matched_text| {
+                    let options = segment.options[index];
+                    let term = segment.dict.get_term(index);
+
+                    self.is_live(segment.offset + index)
+                        && (!options.case_sensitive.unwrap_or_default()
+                            || fuzzy_finder.is_close(matched_text, term))
+                        && options
+                            .capitalization
+                            .unwrap_or(Capitalization::Any)
+                            .accepts(term, matched_text, text)
+                });
+
+                for (start, end, index, distance) in results {
+                    let result = (start, end, segment.offset + index, distance);
+                    let rank = |result: &FuzzyFindResult| {
+                        (result.3, self.get_term(result.2).to_lowercase(), result.2)
+                    };
+
+                    match best.get(&(start, end)) {
+                        Some(known) if rank(known) <= rank(&result) => {}
+                        _ => {
+                            best.insert((start, end), result);
+                        }
+                    }
+                }
+            }
+        }
+
+        best.into_values().collect()
+    }
+
+    /// Adds approximate matches that don't overlap exact matches. Approximate matches
+    /// with the smallest edit distance are selected first
+    fn with_fuzzy_spans<'t>(
+        &'t self,
+        text: &'t str,
+        mut spans: Vec<TaggedContent<'t>>,
+    ) -> Vec<TaggedContent<'t>> {
+        if self.config.fuzzy > 0 {
+            let mut results = self.fuzzy_candidates(text);
+            results.sort_by(|a, b| a.3.cmp(&b.3).then(a.0.cmp(&b.0)).then(b.1.cmp(&a.1)));
+
+            for (start, end, index, distance) in results {
+                if spans
+                    .iter()
+                    .any(|span| start <= span.end && span.start < end)
+                {
+                    continue;
+                }
+
+                spans.push(
+                    self.tagged(text, start, end, index)
+                        .with_fuzzy_match(self.get_term(index), distance),
+                );
+            }
+
+            spans.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+        }
+
+        spans
+    }
+
+    /// Transforms results into tagged spans, sorted by position (longest first)
+    fn spans<'t>(&'t self, text: &'t str, mut results: Vec<FindResult>) -> Vec<TaggedContent<'t>> {
+        results.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        results.dedup();
+
+        results
+            .into_iter()
+            .map(|(start, end, category)| self.tagged(text, start, end, category))
+            .collect()
     }
-}
 
-impl<'a> Find for Tagger<'a> {
-    fn find<S: Into<String>>(&self, input: S) -> Vec<(StartByte, EndByte, DictionaryIndex)> {
-        let finder = &self.finder;
-        let input = input.into();
+    /// Transforms a match into a tagged span holding the matched dictionary entry
+    fn tagged<'t>(
+        &'t self,
+        text: &'t str,
+        start: StartByte,
+        end: EndByte,
+        index: DictionaryIndex,
+    ) -> TaggedContent<'t> {
+        TaggedContent::new(&text[start..end], start, end - 1, self.get_class(index)).with_entry(
+            Cow::Borrowed(self.get_term(index)),
+            self.get_identifier(index).map(Cow::Borrowed),
+            self.get_source(index),
+            self.get_priority(index),
+        )
+    }
+}
 
-        finder
-            .find_iter(&input)
-            .map(|result| (result.start(), result.end(), result.pattern()))
-            .collect::<Vec<(StartByte, EndByte, DictionaryIndex)>>()
+/// Finds matches of every automaton, each one applying its own matching options
+impl Find for Tagger<'_> {
+    fn find(&self, input: &str) -> Vec<FindResult> {
+        self.candidates(input).0
     }
 }
 
-impl<'a> FindWord for Tagger<'a> {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::Tag;
+
+    /// Builds a dictionary from TSV contents, one per dictionary file
+    fn dictionary(files: &[&str]) -> Dictionary {
+        let mut builder = DictionaryBuilder::default();
+
+        for (index, content) in files.iter().enumerate() {
+            builder
+                .read(format!("{}.tsv", index), content.as_bytes())
+                .unwrap();
+        }
+
+        builder.build().unwrap()
+    }
+
+    /// Returns the tagged spans of a text: matched text and class
+    fn spans(tags: Tags) -> Vec<(String, String)> {
+        tags.0
+            .into_iter()
+            .filter_map(|tag| match tag {
+                Tag::Tagged(span) => {
+                    Some((span.original_text.into_owned(), span.class.into_owned()))
+                }
+                Tag::UnTagged(_) => None,
+            })
+            .collect()
+    }
+
+    const OVERLAPPING: &str =
+        "Université de Lyon\tORG\nLyon Part-Dieu\tLOC\nLyon Part-Dieu Sud\tLOC";
+
+    #[test]
+    fn overlapping_matches_are_selected() {
+        let dict = dictionary(&[OVERLAPPING]);
+        let text = "La Université de Lyon Part-Dieu Sud";
+
+        for match_kind in [MatchKind::LeftmostLongest, MatchKind::LeftmostFirst] {
+            let tagger = TaggerBuilder::default()
+                .dictionary(&dict)
+                .match_kind(match_kind)
+                .build()
+                .unwrap();
+
+            assert_eq!(
+                spans(tagger.tag(text)),
+                [("Université de Lyon".to_string(), "ORG".to_string())]
+            );
+        }
+
+        let tagger = TaggerBuilder::default().dictionary(&dict).build().unwrap();
+        assert_eq!(
+            spans(tagger.tag("à Lyon Part-Dieu Sud")),
+            [("Lyon Part-Dieu Sud".to_string(), "LOC".to_string())]
+        );
+    }
+
+    #[test]
+    fn nested_matches_are_layered() {
+        let dict = dictionary(&[OVERLAPPING]);
+        let tagger = TaggerBuilder::default()
+            .dictionary(&dict)
+            .match_kind(MatchKind::Standard)
+            .build()
+            .unwrap();
+
+        let nested = tagger.tag_nested("La Université de Lyon Part-Dieu Sud");
+        let layers = nested
+            .layers
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .map(|span| span.original_text.to_string())
+                    .collect::<Vec<String>>()
+            })
+            .collect::<Vec<Vec<String>>>();
+
+        assert_eq!(
+            layers,
+            [
+                vec!["Université de Lyon"],
+                vec!["Lyon Part-Dieu Sud"],
+                vec!["Lyon Part-Dieu"],
+            ]
+        );
+    }
+
+    #[test]
+    fn word_matching_rejects_matches_inside_words() {
+        let dict = dictionary(&["Université de Lyon\tORG\nPart-Dieu\tLOC\tword_matching=false"]);
+        let tagger = TaggerBuilder::default()
+            .dictionary(&dict)
+            .word_matching(true)
+            .build()
+            .unwrap();
+
+        assert!(spans(tagger.tag("Université de Lyonnais")).is_empty());
+        assert!(spans(tagger.tag("XUniversité de Lyon")).is_empty());
+        assert_eq!(
+            spans(tagger.tag("l'Université de Lyon, à LyonPart-Dieux")),
+            [
+                ("Université de Lyon".to_string(), "ORG".to_string()),
+                ("Part-Dieu".to_string(), "LOC".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn short_terms_keep_their_options() {
+        let dict = dictionary(&["CNRS\tORG\tcase_sensitive=true"]);
+        let tagger = TaggerBuilder::default().dictionary(&dict).build().unwrap();
+
+        assert_eq!(
+            spans(tagger.tag("au CNRS")),
+            [("CNRS".to_string(), "ORG".to_string())]
+        );
+        assert!(spans(tagger.tag("au cnrs")).is_empty());
+    }
+
+    #[test]
+    fn fuzzy_matches_keep_entry_options() {
+        let dict =
+            dictionary(&["Université de Lyon\tORG\tcase_sensitive=true;capitalization=entry"]);
+        let tagger = TaggerBuilder::default()
+            .dictionary(&dict)
+            .fuzzy(1)
+            .build()
+            .unwrap();
+
+        assert!(spans(tagger.tag("la université de lyom")).is_empty());
+        assert!(spans(tagger.tag("la Université de lyom")).is_empty());
+        assert_eq!(
+            spans(tagger.tag("la Université de Lyom")),
+            [("Université de Lyom".to_string(), "ORG".to_string())]
+        );
+    }
+
+    #[test]
+    fn stemmed_matches_keep_entry_options() {
+        let dict = dictionary(&[
+            "Lyon Part-Dieu\tLOC\tcapitalization=initial\nCentre Hospitalier\tORG\tcase_sensitive=true",
+        ]);
+        let tagger = TaggerBuilder::default()
+            .dictionary(&dict)
+            .stemming(Algorithm::French)
+            .build()
+            .unwrap();
+
+        let nested = tagger.tag_nested("à lyon part-dieu");
+        assert!(nested.spans().is_empty());
+        assert_eq!(nested.dropped.len(), 1);
+
+        assert!(spans(tagger.tag("les centres hospitaliers")).is_empty());
+        assert_eq!(
+            spans(tagger.tag("les Centres Hospitaliers")),
+            [("Centres Hospitaliers".to_string(), "ORG".to_string())]
+        );
+    }
+
+    #[test]
+    fn edit_distances_are_bounded() {
+        let dict = dictionary(&[OVERLAPPING]);
+
+        assert!(TaggerBuilder::default()
+            .dictionary(&dict)
+            .fuzzy(MAX_EDIT_DISTANCE + 1)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn spans_kept_by_an_entry_are_not_dropped() {
+        let dict = dictionary(&[
+            "Lyon Part-Dieu\tLOC\tcapitalization=initial\nlyon part-dieu\tLOC\nPart-Dieu\tLOC\tcapitalization=initial",
+        ]);
+        let tagger = TaggerBuilder::default().dictionary(&dict).build().unwrap();
+        let nested = tagger.tag_nested("à lyon part-dieu");
+
+        assert_eq!(nested.spans().len(), 1);
+        assert_eq!(
+            nested
+                .dropped
+                .iter()
+                .map(|span| span.original_text.as_ref())
+                .collect::<Vec<&str>>(),
+            ["part-dieu"]
+        );
+    }
+
+    /// Returns the flat and nested tagging of a text, serialized with the matched entries
+    fn outputs(tagger: &Tagger, text: &str) -> (String, String) {
+        let nested = tagger.tag_nested(text);
+
+        (
+            serde_json::to_string(&tagger.tag(text)).unwrap(),
+            serde_json::to_string(&(nested.layers, nested.dropped)).unwrap(),
+        )
+    }
+
+    #[test]
+    fn updated_taggers_match_rebuilt_taggers() {
+        let base = "Université de Lyon\tORG\nLyon Part-Dieu\tLOC\nLyon Part-Dieu Sud\tLOC\nCentre Léon Bérard\tORG\nHospices Civils de Lyon\tORG\tcapitalization=initial";
+        let replacements = "#!priority=1\nCentre Léon Bérard\tLOC";
+        let additions = "Lyon\tLOC\nPart-Dieu\tLOC\tword_matching=false\nUniversité de Lyon\tLOC";
+        let removed = "Lyon Part-Dieu Sud";
+        let text = "L'Université de Lyon Part-Dieu Sud, le Centre Léon Bérart et les hospices civils de Lyon, à LyonPart-Dieux.";
+
+        let base_dict = dictionary(&[base]);
+        // The empty file keeps the paths of the updates the same as in the rebuilt dictionary
+        let updates_dict = dictionary(&["", replacements, additions]);
+        let final_base = base
+            .lines()
+            .filter(|line| !line.starts_with(&format!("{}\t", removed)))
+            .collect::<Vec<&str>>()
+            .join("\n");
+        let final_dict = dictionary(&[&final_base, replacements, additions]);
+
+        for match_kind in [
+            MatchKind::Standard,
+            MatchKind::LeftmostFirst,
+            MatchKind::LeftmostLongest,
+        ] {
+            let builder = || TaggerBuilder::default().match_kind(match_kind).fuzzy(1);
+
+            let mut updated = builder().dictionary(&base_dict).build().unwrap();
+            assert_eq!(updated.add(&updates_dict).unwrap(), 3);
+            assert!(updated.remove(removed));
+
+            let rebuilt = builder().dictionary(&final_dict).build().unwrap();
+            let expected = outputs(&rebuilt, text);
+
+            assert_eq!(outputs(&updated, text), expected, "{:?}", match_kind);
+
+            updated.compact().unwrap();
+            assert_eq!(outputs(&updated, text), expected, "{:?}", match_kind);
+        }
+    }
+
+    #[test]
+    fn earlier_dictionaries_take_precedence() {
+        let curated = "Lyon Part-Dieu\tLOC";
+        let big = "Lyon Part-Dieu\tORG\nLyon Part-Dieu Sud\tORG";
+        let text = "à Lyon Part-Dieu Sud et Lyon Part-Dieu";
+
+        let dict = dictionary(&[curated, big]);
+        let tagger = TaggerBuilder::default().dictionary(&dict).build().unwrap();
+        assert_eq!(
+            spans(tagger.tag(text)),
+            [
+                ("Lyon Part-Dieu".to_string(), "LOC".to_string()),
+                ("Lyon Part-Dieu".to_string(), "LOC".to_string())
+            ]
+        );
+
+        let dict = dictionary(&[big, curated]);
+        let tagger = TaggerBuilder::default().dictionary(&dict).build().unwrap();
+        assert_eq!(
+            spans(tagger.tag(text)),
+            [
+                ("Lyon Part-Dieu Sud".to_string(), "ORG".to_string()),
+                ("Lyon Part-Dieu".to_string(), "ORG".to_string())
+            ]
+        );
+
+        let dict = dictionary(&[big, &format!("#!priority=1\n{}", curated)]);
+        let tagger = TaggerBuilder::default().dictionary(&dict).build().unwrap();
+        assert_eq!(
+            spans(tagger.tag(text))[0],
+            ("Lyon Part-Dieu".to_string(), "LOC".to_string())
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use crate::format::PunctuationTokenizer;
+use crate::traits::{Find, HeapSize, Tokenizer};
+use crate::types::*;
+use aho_corasick::{AhoCorasick, BuildError, MatchKind};
+use std::collections::HashMap;
+
+/// Token id given to input tokens missing from the dictionary
+const UNKNOWN_TOKEN: char = '\u{0}';
+
+/// Finds dictionary terms as sequences of tokens. Input and dictionary terms are split by the same
+/// tokenizer (PunctuationTokenizer), tokens are mapped to ids and Aho-Corasick runs over the token ids, so that matches
+/// are aligned on tokens by construction.
+pub struct TokenFinder {
+    vocabulary: HashMap<String, char>,
+    automaton: AhoCorasick,
+    case_sensitive: bool,
+}
+
+impl TokenFinder {
+    pub fn new(terms: &[&str], case_sensitive: bool) -> Result<TokenFinder, BuildError> {
+        let mut vocabulary: HashMap<String, char> = HashMap::new();
+
+        let patterns = terms
+            .iter()
+            .map(|term| {
+                PunctuationTokenizer
+                    .tokenize(term)
+                    .into_iter()
+                    .map(|(start, end)| {
+                        let token = normalize(&term[start..end], case_sensitive);
+                        let next_id = vocabulary.len() as u32 + 1;
+
+                        *vocabulary.entry(token).or_insert_with(|| token_id(next_id))
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>();
+
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(patterns)?;
+
+        Ok(TokenFinder {
+            vocabulary,
+            automaton,
+            case_sensitive,
+        })
+    }
+}
+
+/// Encodes a token id as a char. UTF-8 being self-synchronizing, a pattern of token chars can
+/// only match on token boundaries
+fn token_id(id: u32) -> char {
+    let code = if id >= 0xD800 { id + 0x800 } else { id };
+
+    char::from_u32(code).expect("The vocabulary can't exceed the number of unicode scalar values")
+}
+
+fn normalize(token: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        token.to_string()
+    } else {
+        token.to_lowercase()
+    }
+}
+
+impl Find for TokenFinder {
+    fn find(&self, input: &str) -> Vec<FindResult> {
+        let tokens = PunctuationTokenizer.tokenize(input);
+
+        // Position of each token id in the encoded input
+        let mut positions = Vec::with_capacity(tokens.len());
+        let mut encoded_input = String::with_capacity(tokens.len());
+
+        for (start, end) in &tokens {
+            positions.push(encoded_input.len());
+
+            let token = normalize(&input[*start..*end], self.case_sensitive);
+            encoded_input.push(*self.vocabulary.get(&token).unwrap_or(&UNKNOWN_TOKEN));
+        }
+
+        self.automaton
+            .find_overlapping_iter(&encoded_input)
+            .filter(|result| result.start() < result.end())
+            .filter_map(|result| {
+                let first = positions.binary_search(&result.start()).ok()?;
+                let last = match positions.binary_search(&result.end()) {
+                    Ok(next) => next - 1,
+                    Err(_) => tokens.len() - 1,
+                };
+
+                Some((tokens[first].0, tokens[last].1, result.pattern().as_usize()))
+            })
+            .collect::<Vec<FindResult>>()
+    }
+}
+
+impl HeapSize for TokenFinder {
+    fn heap_bytes(&self) -> usize {
+        let vocabulary_bytes = self
+            .vocabulary
+            .keys()
+            .map(|token| token.capacity() + std::mem::size_of::<(String, char)>())
+            .sum::<usize>();
+
+        self.automaton.memory_usage() + vocabulary_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the text and the pattern of each match
+    fn matches<'t>(finder: &TokenFinder, input: &'t str) -> Vec<(&'t str, usize)> {
+        let mut results = finder.find(input);
+        results.sort();
+
+        results
+            .into_iter()
+            .map(|(start, end, pattern)| (&input[start..end], pattern))
+            .collect()
+    }
+
+    #[test]
+    fn terms_match_whole_tokens() {
+        let finder = TokenFinder::new(&["Université de Lyon", "Lyon", "Ly", "on"], false).unwrap();
+
+        assert_eq!(
+            matches(&finder, "l'Université de Lyon, à Lyonnais"),
+            [("Université de Lyon", 0), ("Lyon", 1)]
+        );
+        assert!(matches(&finder, "Université de Lyonnais").is_empty());
+    }
+
+    #[test]
+    fn punctuation_is_a_token() {
+        let finder = TokenFinder::new(&["Lyon", "Lyon ,", "Lyon."], false).unwrap();
+
+        assert_eq!(
+            matches(&finder, "(Lyon), Lyon,Lyon."),
+            [
+                ("Lyon", 0),
+                ("Lyon", 0),
+                ("Lyon,", 1),
+                ("Lyon", 0),
+                ("Lyon.", 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn inner_hyphens_are_kept() {
+        let finder = TokenFinder::new(&["Part-Dieu", "Dieu"], false).unwrap();
+
+        assert_eq!(
+            matches(&finder, "Lyon Part-Dieu - Dieu"),
+            [("Part-Dieu", 0), ("Dieu", 1)]
+        );
+    }
+
+    #[test]
+    fn case_is_folded_unless_case_sensitive() {
+        let terms = ["Université de Lyon", "CNRS"];
+        let input = "UNIVERSITÉ de lyon et Cnrs";
+
+        let finder = TokenFinder::new(&terms, false).unwrap();
+        assert_eq!(
+            matches(&finder, input),
+            [("UNIVERSITÉ de lyon", 0), ("Cnrs", 1)]
+        );
+
+        let finder = TokenFinder::new(&terms, true).unwrap();
+        assert!(matches(&finder, input).is_empty());
+    }
+}
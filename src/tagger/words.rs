@@ -0,0 +1,42 @@
+use crate::types::*;
+
+/// Returns the byte positions of the words of a text. Words are separated by whitespaces and punctuation
+pub fn words(input: &str) -> Vec<(StartByte, EndByte)> {
+    let mut words = vec![];
+    let mut word_start: Option<usize> = None;
+
+    for (index, c) in input.char_indices() {
+        let is_boundary = c.is_whitespace() || c.is_ascii_punctuation();
+
+        match (word_start, is_boundary) {
+            (Some(start), true) => {
+                words.push((start, index));
+                word_start = None;
+            }
+            (None, false) => word_start = Some(index),
+            _ => {}
+        }
+    }
+
+    if let Some(start) = word_start {
+        words.push((start, input.len()));
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn punctuation_separates_words() {
+        let input = "l'Université  (Lyon 2), Bron…";
+        let words = words(input)
+            .into_iter()
+            .map(|(start, end)| &input[start..end])
+            .collect::<Vec<&str>>();
+
+        assert_eq!(words, ["l", "Université", "Lyon", "2", "Bron…"]);
+    }
+}
@@ -1,27 +1,26 @@
 use crate::tagger::Tag;
 use crate::types::*;
 
+#[allow(dead_code)]
 pub trait Annotate {
-    fn annotate(&self) -> Vec<Tag>;
+    fn annotate(&self) -> Vec<Tag<'_>>;
 }
 
 /// Find patterns in a string and return their positions in bytes
 pub trait Find {
-    fn find<S: Into<String>>(&self, input: S) -> Vec<FindResult>;
+    fn find(&self, input: &str) -> Vec<FindResult>;
 }
 
 /// Find words in a string and return their positions in bytes
 pub trait FindWord: Find {
-    fn find_word<S: Into<String>>(&self, input: S) -> Vec<FindResult> {
-        let input = input.into();
-
-        let results = self.find(&input);
+    fn find_word(&self, input: &str) -> Vec<FindResult> {
+        let results = self.find(input);
 
         results
             .into_iter()
             .filter(|(start, end, _)| {
-                let previous_char = self.get_previous_char(*start, &input);
-                let next_char = self.get_next_char(*end, &input);
+                let previous_char = self.get_previous_char(*start, input);
+                let next_char = self.get_next_char(*end, input);
 
                 match (previous_char, next_char) {
                     (None, Some(next_char)) => {
@@ -43,7 +42,7 @@ pub trait FindWord: Find {
                     (None, None) => return true,
                 };
 
-                return false;
+                false
             })
             .collect::<Vec<(StartByte, EndByte, DictionaryIndex)>>()
     }
@@ -52,43 +51,25 @@ pub trait FindWord: Find {
         input.is_whitespace() | input.is_ascii_punctuation()
     }
 
+    /// Returns the character before a byte position
     fn get_previous_char(&self, index: usize, input: &str) -> Option<char> {
-        let mut mindex = index;
-        if index < 1 {
-            None
-        } else {
-            while !input.is_char_boundary(mindex) {
-                if mindex > 0 {
-                    mindex -= 1
-                } else {
-                    return None;
-                }
-            }
-
-            let chars = input[mindex..index].chars().collect::<Vec<char>>();
-
-            chars.last().map(|c| c.clone())
-        }
+        input.get(..index)?.chars().next_back()
     }
 
+    /// Returns the character at a byte position
     fn get_next_char(&self, index: usize, input: &str) -> Option<char> {
-        let mut mindex = index;
-        if index >= input.len() {
-            None
-        } else {
-            while !input.is_char_boundary(mindex) {
-                if mindex > 0 {
-                    mindex += 1
-                } else {
-                    return None;
-                }
-            }
+        input.get(index..)?.chars().next()
+    }
+}
 
-            let chars = input[mindex..index].chars().collect::<Vec<char>>();
+/// Report the memory used on the heap
+pub trait HeapSize {
+    fn heap_bytes(&self) -> usize;
+}
 
-            chars.first().map(|c| c.clone())
-        }
-    }
+/// Splits a text into tokens and return their positions in bytes (exclusive ends)
+pub trait Tokenizer {
+    fn tokenize(&self, input: &str) -> Vec<(StartByte, EndByte)>;
 }
 
 pub trait PrettyDisplay {
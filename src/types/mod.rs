@@ -8,13 +8,21 @@ pub type EndByte = usize;
 pub type DictionaryIndex = usize;
 /// Represents a set of Entry of a Dictionary
 pub type Entries = Vec<DictionaryEntry>;
-/// Represents a term in a Dictionary
-pub type Term = String;
 /// Represents a set of terms in a Dictionary
-pub type Terms<'a> = Vec<&'a Term>;
+pub type Terms<'a> = Vec<&'a str>;
 /// Represents a class in a Dictionary
 pub type Class = String;
 /// Represents a set of classes in a Dictionary
-pub type Classes<'a> = Vec<&'a Class>;
+pub type Classes<'a> = Vec<&'a str>;
+/// Represents an interned class of a Dictionary
+pub type ClassId = u32;
 /// Represents a FindResult
 pub type FindResult = (StartByte, EndByte, DictionaryIndex);
+/// Represents the index of the dictionary file an entry comes from
+pub type SourceIndex = u16;
+/// Represents the priority of a dictionary file
+pub type Priority = i32;
+/// Represents the edit distance between a piece of text and a dictionary term
+pub type EditDistance = u8;
+/// Represents an approximate FindResult
+pub type FuzzyFindResult = (StartByte, EndByte, DictionaryIndex, EditDistance);